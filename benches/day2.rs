@@ -0,0 +1,42 @@
+use aoc22::day2::{self, ROCK_PAPER_SCISSORS};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+/// The puzzle input, repeated to make a guide large enough to measure.
+fn guide() -> String {
+    day2::INPUT.repeat(20)
+}
+
+/// The same guide with two spaces between the columns.
+/// It scores the same, but every line falls back from the lookup table to the full parser.
+fn fallback_guide() -> String {
+    guide().replace(' ', "  ")
+}
+
+fn score(c: &mut Criterion) {
+    let rules = &*ROCK_PAPER_SCISSORS;
+    let guide = guide();
+    let fallback = fallback_guide();
+
+    let mut group = c.benchmark_group("day2");
+    group.throughput(Throughput::Elements(guide.lines().count() as u64));
+    for (name, input) in [("lookup", &guide), ("fallback", &fallback)] {
+        group.bench_with_input(BenchmarkId::new("part1", name), input, |b, input| {
+            b.iter(|| day2::part1_bytes(input.as_bytes(), rules).expect("must score"))
+        });
+        group.bench_with_input(BenchmarkId::new("part2", name), input, |b, input| {
+            b.iter(|| day2::part2_bytes(input.as_bytes(), rules).expect("must score"))
+        });
+    }
+
+    // Scoring as it was before the lookup table: parse every round, then work out each score from the rules.
+    group.bench_with_input(BenchmarkId::new("part1", "baseline"), &guide, |b, input| {
+        b.iter(|| day2::part1_parsed(input, rules).expect("must score"))
+    });
+    group.bench_with_input(BenchmarkId::new("part2", "baseline"), &guide, |b, input| {
+        b.iter(|| day2::part2_parsed(input, rules).expect("must score"))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, score);
+criterion_main!(benches);
@@ -0,0 +1,65 @@
+use std::collections::HashSet;
+
+use aoc22::day3::{self, ItemIndex, ItemSet};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+/// The puzzle input, repeated to make an inventory large enough to measure.
+fn inventory() -> String {
+    day3::INPUT.repeat(20)
+}
+
+/// Split a rucksack into its two compartments.
+fn compartments(line: &str) -> (&str, &str) {
+    line.split_at(line.len() / 2)
+}
+
+/// Find the item type shared by each rucksack's compartments by hashing every item type,
+/// as rucksacks were stored before item sets.
+fn shared_hash_set(inventory: &str) -> usize {
+    inventory
+        .lines()
+        .map(|line| {
+            let (first, second) = compartments(line);
+            let first = first.chars().collect::<HashSet<_>>();
+            let second = second.chars().collect::<HashSet<_>>();
+            first.intersection(&second).count()
+        })
+        .sum()
+}
+
+/// Find the item type shared by each rucksack's compartments with item sets.
+fn shared_item_set(inventory: &str) -> usize {
+    inventory
+        .lines()
+        .map(|line| {
+            let (first, second) = compartments(line);
+            let mut items = ItemIndex::new();
+            let first = ItemSet::parse(first, &mut items);
+            let second = ItemSet::parse(second, &mut items);
+            first.intersection(&second).len()
+        })
+        .sum()
+}
+
+fn shared(c: &mut Criterion) {
+    let inventory = inventory();
+
+    let mut group = c.benchmark_group("day3");
+    group.throughput(Throughput::Elements(inventory.lines().count() as u64));
+    group.bench_function("shared/hash_set", |b| {
+        b.iter(|| shared_hash_set(&inventory))
+    });
+    group.bench_function("shared/item_set", |b| {
+        b.iter(|| shared_item_set(&inventory))
+    });
+    group.bench_function("part1", |b| {
+        b.iter(|| day3::part1(&inventory).expect("must score"))
+    });
+    group.bench_function("part2", |b| {
+        b.iter(|| day3::part2(&inventory).expect("must score"))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, shared);
+criterion_main!(benches);
@@ -0,0 +1,124 @@
+use std::{convert::Infallible, fmt, str::FromStr};
+
+/// The answer to one part of a puzzle.
+///
+/// Most puzzles have a numeric answer, but some ask for a word,
+/// or for text that is drawn as ASCII art across several lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    /// A signed or unsigned number.
+    Number(i128),
+
+    /// A single line of text.
+    Text(String),
+
+    /// ASCII art drawn across several lines.
+    Grid(Vec<String>),
+}
+
+impl Answer {
+    /// Construct a grid answer from its rows.
+    pub fn grid<S: Into<String>>(rows: impl IntoIterator<Item = S>) -> Self {
+        Self::Grid(rows.into_iter().map(Into::into).collect())
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Number(number) => write!(f, "{number}"),
+            Answer::Text(text) => write!(f, "{text}"),
+            Answer::Grid(rows) => write!(f, "{}", rows.join("\n")),
+        }
+    }
+}
+
+/// Answers given on the command line are numbers if they parse as one, grids if they span several lines,
+/// and text otherwise.
+impl FromStr for Answer {
+    type Err = Infallible;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        if input.contains('\n') {
+            return Ok(Answer::grid(input.lines()));
+        }
+        Ok(match input.parse() {
+            Ok(number) => Answer::Number(number),
+            Err(_) => Answer::Text(input.to_owned()),
+        })
+    }
+}
+
+macro_rules! number_answer {
+    ($($number:ty),*) => {$(
+        impl From<$number> for Answer {
+            fn from(number: $number) -> Self {
+                Answer::Number(number as i128)
+            }
+        }
+
+        impl PartialEq<$number> for Answer {
+            fn eq(&self, other: &$number) -> bool {
+                matches!(self, Answer::Number(number) if *number == *other as i128)
+            }
+        }
+    )*};
+}
+
+number_answer!(usize, u64, u32, isize, i64, i32);
+
+impl From<String> for Answer {
+    fn from(text: String) -> Self {
+        Answer::Text(text)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(text: &str) -> Self {
+        Answer::Text(text.to_owned())
+    }
+}
+
+impl PartialEq<&str> for Answer {
+    fn eq(&self, other: &&str) -> bool {
+        match self {
+            Answer::Number(_) => false,
+            Answer::Text(text) => text == other,
+            Answer::Grid(rows) => rows.join("\n") == *other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display() {
+        assert_eq!(Answer::from(24000usize).to_string(), "24000");
+        assert_eq!(Answer::from(-12i64).to_string(), "-12");
+        assert_eq!(Answer::from("CMZ").to_string(), "CMZ");
+        assert_eq!(Answer::grid(["#..#", ".##."]).to_string(), "#..#\n.##.");
+    }
+
+    #[test]
+    fn compare() {
+        assert_eq!(Answer::from(45000usize), 45000usize);
+        assert_eq!(Answer::from(-3i32), -3i64);
+        assert_eq!(Answer::from(5u64), Answer::from(5i32));
+        assert_ne!(Answer::from("15"), 15usize);
+        assert_eq!(Answer::from("MCD"), "MCD");
+        assert_eq!(Answer::grid(["#.", ".#"]), "#.\n.#");
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!("157".parse::<Answer>(), Ok(Answer::Number(157)));
+        assert_eq!("-2".parse::<Answer>(), Ok(Answer::Number(-2)));
+        assert_eq!("CMZ".parse::<Answer>(), Ok(Answer::from("CMZ")));
+        assert_eq!(
+            "#..#\r\n.##.\n".parse::<Answer>(),
+            Ok(Answer::grid(["#..#", ".##."]))
+        );
+    }
+}
@@ -1,8 +1,41 @@
+use std::{
+    cmp::Ordering,
+    io::{self, BufRead},
+};
+
+use itertools::process_results;
 use stable_eyre::{eyre::Context, Report};
 
+use crate::{Answer, ParseError, Solution};
+
 /// The puzzle input.
 pub const INPUT: &str = include_str!("input/day1");
 
+/// The solution for day 1.
+pub struct Day1;
+
+impl Solution for Day1 {
+    fn day(&self) -> u8 {
+        1
+    }
+
+    fn title(&self) -> &'static str {
+        "Calorie Counting"
+    }
+
+    fn input(&self) -> &'static str {
+        INPUT
+    }
+
+    fn part1(&self, input: &str) -> Result<Answer, Report> {
+        part1(input).map(Answer::from)
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, Report> {
+        part2(input).map(Answer::from)
+    }
+}
+
 /// Given the input, how many total calories is carried by the elf carrying the most calories?
 ///
 /// Example input:
@@ -35,7 +68,14 @@ pub const INPUT: &str = include_str!("input/day1");
 ///
 /// In the example above, this is 24000 (carried by the fourth Elf).
 pub fn part1(input: &str) -> Result<usize, Report> {
-    group_stashes(input).map(|stashes| stashes.into_iter().max().unwrap_or_default())
+    part1_reader(input.as_bytes())
+}
+
+/// Solve [`part1`], reading the input one line at a time.
+pub fn part1_reader(reader: impl BufRead) -> Result<usize, Report> {
+    process_results(stashes(reader), |stashes| {
+        stashes.map(|stash| stash.total).max().unwrap_or_default()
+    })
 }
 
 /// By the time you calculate the answer to the Elves' question,
@@ -51,12 +91,46 @@ pub fn part1(input: &str) -> Result<usize, Report> {
 ///
 /// Find the top three Elves carrying the most Calories. How many Calories are those Elves carrying in total?
 pub fn part2(input: &str) -> Result<usize, Report> {
-    group_stashes(input).map(|stashes| stashes.into_iter().multi_max(3).into_iter().sum())
+    part2_reader(input.as_bytes())
 }
 
-fn parse_calories(line: &str) -> Result<usize, Report> {
+/// Solve [`part2`], reading the input one line at a time.
+pub fn part2_reader(reader: impl BufRead) -> Result<usize, Report> {
+    process_results(stashes(reader), |stashes| {
+        stashes
+            .map(|stash| stash.total)
+            .multi_max(3)
+            .into_iter()
+            .sum()
+    })
+}
+
+/// The elves carrying the most calories, along with what they're carrying.
+///
+/// Elves are returned with the one carrying the most calories first.
+pub fn top_stashes(reader: impl BufRead, count: usize) -> Result<Vec<Stash>, Report> {
+    process_results(stashes(reader), |stashes| {
+        let mut top = stashes.multi_max_by_key(count, |stash| stash.total);
+        top.reverse();
+        top
+    })
+}
+
+/// The stash carried by the elf at the given index, if there is such an elf.
+pub fn find_stash(reader: impl BufRead, index: usize) -> Result<Option<Stash>, Report> {
+    for stash in stashes(reader) {
+        let stash = stash?;
+        if stash.index == index {
+            return Ok(Some(stash));
+        }
+    }
+    Ok(None)
+}
+
+/// Parse the calories of a food item from line `number` of the input.
+fn parse_calories(number: usize, line: &str) -> Result<usize, ParseError> {
     line.parse()
-        .wrap_err_with(|| format!("parse input '{line}'"))
+        .map_err(|err| ParseError::line(format!("invalid calories ({err})"), number, line))
 }
 
 /// Given input in the form:
@@ -69,65 +143,243 @@ fn parse_calories(line: &str) -> Result<usize, Report> {
 /// <NUMBER>
 /// ```
 ///
-/// This function sums each consecutive number, creating a new element in the vec when a space is encountered.
-fn group_stashes(input: &str) -> Result<Vec<usize>, Report> {
-    input
-        .lines()
-        .try_fold(Vec::new(), |mut elves, food| -> Result<_, Report> {
+/// This function groups each consecutive number, yielding the stash for an elf when a space is encountered.
+/// Only the lines making up the current elf's stash are held in memory at any time.
+pub fn stashes<R: BufRead>(reader: R) -> Stashes<R> {
+    Stashes {
+        lines: reader.lines(),
+        line_number: 0,
+        next_index: 0,
+    }
+}
+
+/// The food carried by a single elf.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stash {
+    /// The position of the elf in the input, starting at 0.
+    pub index: usize,
+
+    /// The calories of each food item the elf is carrying, in input order.
+    pub items: Vec<usize>,
+
+    /// The total calories the elf is carrying.
+    pub total: usize,
+}
+
+impl Stash {
+    fn new(index: usize) -> Self {
+        Self {
+            index,
+            items: Vec::new(),
+            total: 0,
+        }
+    }
+
+    fn add(&mut self, calories: usize) {
+        self.items.push(calories);
+        self.total += calories;
+    }
+}
+
+/// Iterates over the stash carried by each elf.
+///
+/// Constructed with [`stashes`].
+pub struct Stashes<R> {
+    lines: io::Lines<R>,
+    line_number: usize,
+    next_index: usize,
+}
+
+impl<R: BufRead> Iterator for Stashes<R> {
+    type Item = Result<Stash, Report>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut stash: Option<Stash> = None;
+        for line in self.lines.by_ref() {
+            self.line_number += 1;
+            let food = match line.wrap_err("read input") {
+                Ok(food) => food,
+                Err(err) => return Some(Err(err)),
+            };
+
+            // Runs of blank lines only separate elves, they don't make up an elf of their own.
             if food.is_empty() {
-                elves.push(0);
-            } else {
-                let calories = parse_calories(food)?;
-                match elves.last_mut() {
-                    Some(elf) => *elf += calories,
-                    None => elves.push(calories),
+                match stash {
+                    Some(_) => break,
+                    None => continue,
                 }
             }
-            Ok(elves)
-        })
+
+            match parse_calories(self.line_number, &food) {
+                Ok(calories) => stash
+                    .get_or_insert_with(|| Stash::new(self.next_index))
+                    .add(calories),
+                Err(err) => return Some(Err(err.into())),
+            }
+        }
+
+        if stash.is_some() {
+            self.next_index += 1;
+        }
+        stash.map(Ok)
+    }
 }
 
-trait MultiMaxer<T>
-where
-    T: Ord,
-{
+/// Collects the greatest or least N values from an iterator.
+///
+/// Values are returned in ascending order. Each method runs in `O(n log N)` time
+/// and only ever holds N values in memory.
+pub trait MultiMaxer: Iterator + Sized {
     /// Construct a vector which collects the top N values from the iterator.
-    fn multi_max(self, count: usize) -> Vec<T>;
+    fn multi_max(self, count: usize) -> Vec<Self::Item>
+    where
+        Self::Item: Ord,
+    {
+        self.multi_max_by(count, Ord::cmp)
+    }
+
+    /// Construct a vector which collects the top N values from the iterator,
+    /// ordered by the given comparison function.
+    fn multi_max_by<F>(self, count: usize, compare: F) -> Vec<Self::Item>
+    where
+        F: FnMut(&Self::Item, &Self::Item) -> Ordering,
+    {
+        let mut heap = BoundedHeap::new(count, compare);
+        for value in self {
+            heap.push(value);
+        }
+        heap.into_sorted_vec()
+    }
+
+    /// Construct a vector which collects the top N values from the iterator,
+    /// ordered by the key extracted from each value.
+    fn multi_max_by_key<K, F>(self, count: usize, mut key: F) -> Vec<Self::Item>
+    where
+        K: Ord,
+        F: FnMut(&Self::Item) -> K,
+    {
+        self.multi_max_by(count, |a, b| key(a).cmp(&key(b)))
+    }
+
+    /// Construct a vector which collects the bottom N values from the iterator.
+    fn multi_min(self, count: usize) -> Vec<Self::Item>
+    where
+        Self::Item: Ord,
+    {
+        self.multi_min_by(count, Ord::cmp)
+    }
+
+    /// Construct a vector which collects the bottom N values from the iterator,
+    /// ordered by the given comparison function.
+    fn multi_min_by<F>(self, count: usize, mut compare: F) -> Vec<Self::Item>
+    where
+        F: FnMut(&Self::Item, &Self::Item) -> Ordering,
+    {
+        // The smallest values are the largest values when the ordering is flipped,
+        // they just come out in descending order.
+        let mut mins = self.multi_max_by(count, |a, b| compare(b, a));
+        mins.reverse();
+        mins
+    }
+
+    /// Construct a vector which collects the bottom N values from the iterator,
+    /// ordered by the key extracted from each value.
+    fn multi_min_by_key<K, F>(self, count: usize, mut key: F) -> Vec<Self::Item>
+    where
+        K: Ord,
+        F: FnMut(&Self::Item) -> K,
+    {
+        self.multi_min_by(count, |a, b| key(a).cmp(&key(b)))
+    }
+}
+
+impl<I: Iterator> MultiMaxer for I {}
+
+/// A binary min-heap that holds at most `capacity` values.
+///
+/// The smallest value retained so far is always at the root,
+/// so deciding whether a new value belongs in the top N is a single comparison against it.
+struct BoundedHeap<T, F> {
+    values: Vec<T>,
+    capacity: usize,
+    compare: F,
 }
 
-impl<T, I> MultiMaxer<T> for I
+impl<T, F> BoundedHeap<T, F>
 where
-    T: Ord,
-    I: Iterator<Item = T>,
+    F: FnMut(&T, &T) -> Ordering,
 {
-    /// Construct a vector which collects the top N values from the iterator.
-    fn multi_max(self, count: usize) -> Vec<T> {
-        let mut maxes = Vec::with_capacity(count);
-
-        for current_value in self {
-            if maxes.len() < count {
-                maxes.push(current_value);
-            } else {
-                for prev_max in maxes.iter_mut() {
-                    if matches!(Ord::cmp(prev_max, &current_value), std::cmp::Ordering::Less) {
-                        *prev_max = current_value;
-                        break;
-                    }
-                }
+    fn new(capacity: usize, compare: F) -> Self {
+        Self {
+            values: Vec::new(),
+            capacity,
+            compare,
+        }
+    }
+
+    /// Add the value to the heap, evicting the smallest value if the heap is full
+    /// and the new value is greater than it.
+    fn push(&mut self, value: T) {
+        if self.values.len() < self.capacity {
+            self.values.push(value);
+            self.sift_up(self.values.len() - 1);
+        } else if let Some(smallest) = self.values.first_mut() {
+            if (self.compare)(&value, smallest) == Ordering::Greater {
+                *smallest = value;
+                self.sift_down(0);
             }
+        }
+    }
+
+    /// Consume the heap, returning its values in ascending order.
+    fn into_sorted_vec(self) -> Vec<T> {
+        let Self {
+            mut values,
+            mut compare,
+            ..
+        } = self;
+        values.sort_by(&mut compare);
+        values
+    }
 
-            // To keep the "searching for a value to replace" logic simpler,
-            // just ensure that the lowest max value is the earliest item in the vec
-            // each time we modify it.
-            maxes.sort()
+    /// Move the value at `index` towards the root until its parent is no greater than it.
+    fn sift_up(&mut self, mut index: usize) {
+        while index > 0 {
+            let parent = (index - 1) / 2;
+            if (self.compare)(&self.values[index], &self.values[parent]) != Ordering::Less {
+                break;
+            }
+            self.values.swap(index, parent);
+            index = parent;
         }
+    }
+
+    /// Move the value at `index` away from the root until neither child is smaller than it.
+    fn sift_down(&mut self, mut index: usize) {
+        loop {
+            let mut smallest = index;
+            for child in [2 * index + 1, 2 * index + 2] {
+                if child < self.values.len()
+                    && (self.compare)(&self.values[child], &self.values[smallest]) == Ordering::Less
+                {
+                    smallest = child;
+                }
+            }
 
-        maxes
+            if smallest == index {
+                break;
+            }
+            self.values.swap(index, smallest);
+            index = smallest;
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use assert_matches::assert_matches;
+    use proptest::{collection::vec, prelude::*};
+
     use super::*;
 
     #[test]
@@ -160,13 +412,92 @@ mod tests {
         assert_eq!(maxes, expected);
 
         let inputs: Vec<usize> = vec![];
-        let expected = vec![];
+        let expected: Vec<usize> = vec![];
+        let maxes = inputs.into_iter().multi_max(3);
+        assert_eq!(maxes, expected);
+
+        let inputs = vec![500, 100, 400];
+        let expected: Vec<usize> = vec![];
+        let maxes = inputs.into_iter().multi_max(0);
+        assert_eq!(maxes, expected);
+    }
+
+    #[test]
+    fn test_multi_max_replaces_smallest() {
+        // The old implementation replaced the first smaller value it found rather than the smallest.
+        let inputs = vec![300, 100, 200, 250];
+        let expected = vec![200, 250, 300];
         let maxes = inputs.into_iter().multi_max(3);
         assert_eq!(maxes, expected);
     }
 
     #[test]
-    fn test_group_stashes() -> Result<(), Report> {
+    fn test_multi_max_exact_capacity() {
+        // Found by proptest against an earlier heap, which mishandled being filled exactly to capacity.
+        assert_eq!(vec![0, 1].into_iter().multi_max(2), vec![0, 1]);
+        assert_eq!(vec![1, 0].into_iter().multi_max(2), vec![0, 1]);
+        assert_eq!(vec![0, 1].into_iter().multi_min(2), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_multi_min() {
+        let inputs = vec![100, 200, 300, 400, 100, 500];
+        let expected = vec![100, 100, 200];
+        let mins = inputs.into_iter().multi_min(3);
+        assert_eq!(mins, expected);
+    }
+
+    #[test]
+    fn test_multi_max_by_key() {
+        let inputs = vec![("a", 3), ("b", 1), ("c", 2), ("d", 5)];
+        let expected = vec![("c", 2), ("a", 3), ("d", 5)];
+        let maxes = inputs.into_iter().multi_max_by_key(3, |(_, total)| *total);
+        assert_eq!(maxes, expected);
+
+        let inputs = vec![("a", 3), ("b", 1), ("c", 2), ("d", 5)];
+        let expected = vec![("b", 1), ("c", 2)];
+        let mins = inputs.into_iter().multi_min_by_key(2, |(_, total)| *total);
+        assert_eq!(mins, expected);
+    }
+
+    proptest! {
+        #[test]
+        fn multi_max_matches_sort(values in vec(any::<i32>(), 0..500), count in 0usize..600) {
+            let mut expected = values.clone();
+            expected.sort();
+            let expected = expected.split_off(expected.len().saturating_sub(count));
+            prop_assert_eq!(values.into_iter().multi_max(count), expected);
+        }
+
+        #[test]
+        fn multi_min_matches_sort(values in vec(any::<i32>(), 0..500), count in 0usize..600) {
+            let mut expected = values.clone();
+            expected.sort();
+            expected.truncate(count);
+            prop_assert_eq!(values.into_iter().multi_min(count), expected);
+        }
+
+        #[test]
+        fn multi_max_by_key_matches_sort(values in vec((any::<u8>(), any::<u8>()), 0..500), count in 0usize..600) {
+            // Values with equal keys may be chosen in any order, so only the keys are compared.
+            let mut expected = values.iter().map(|(key, _)| *key).collect::<Vec<_>>();
+            expected.sort();
+            let expected = expected.split_off(expected.len().saturating_sub(count));
+            let maxes = values.into_iter().multi_max_by_key(count, |(key, _)| *key);
+            prop_assert_eq!(maxes.into_iter().map(|(key, _)| key).collect::<Vec<_>>(), expected);
+        }
+
+        #[test]
+        fn multi_min_by_matches_sort(values in vec(any::<i64>(), 0..500), count in 0usize..600) {
+            let mut expected = values.clone();
+            expected.sort_by(|a, b| b.cmp(a));
+            expected.truncate(count);
+            prop_assert_eq!(values.into_iter().multi_min_by(count, |a, b| b.cmp(a)), expected);
+        }
+    }
+
+    #[test]
+    fn test_stashes() -> Result<(), Report> {
         let input = r#"100
 100
 100
@@ -175,8 +506,70 @@ mod tests {
 
 100
 100"#;
-        let expected = vec![300, 400, 200];
-        assert_eq!(group_stashes(input)?, expected);
+        let expected = vec![
+            Stash {
+                index: 0,
+                items: vec![100, 100, 100],
+                total: 300,
+            },
+            Stash {
+                index: 1,
+                items: vec![400],
+                total: 400,
+            },
+            Stash {
+                index: 2,
+                items: vec![100, 100],
+                total: 200,
+            },
+        ];
+        let stashes = stashes(input.as_bytes()).collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(stashes, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_stashes_blank_runs() -> Result<(), Report> {
+        let input = "\n100\r\n\r\n\r\n200\n300\n\n";
+        let expected = vec![(0, 100), (1, 500)];
+        let totals = stashes(input.as_bytes())
+            .map(|stash| stash.map(|stash| (stash.index, stash.total)))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(totals, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_stashes_invalid() {
+        let mut stashes = stashes("100\n\nabc\n".as_bytes());
+        assert_matches!(stashes.next(), Some(Ok(Stash { total: 100, .. })));
+
+        let err = stashes.next().and_then(Result::err).expect("must error");
+        let err = err
+            .downcast_ref::<ParseError>()
+            .expect("must be a parse error");
+        assert_eq!(err.line_number(), 3);
+        assert_eq!(err.column(), 1);
+        assert_eq!(err.text(), "abc");
+    }
+
+    const EXAMPLE: &str = "1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000\n";
+
+    #[test]
+    fn test_top_stashes() -> Result<(), Report> {
+        let top = top_stashes(EXAMPLE.as_bytes(), 3)?
+            .into_iter()
+            .map(|stash| (stash.index, stash.total))
+            .collect::<Vec<_>>();
+        assert_eq!(top, vec![(3, 24000), (2, 11000), (4, 10000)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_stash() -> Result<(), Report> {
+        let stash = find_stash(EXAMPLE.as_bytes(), 2)?;
+        assert_eq!(stash.map(|stash| stash.items), Some(vec![5000, 6000]));
+        assert_eq!(find_stash(EXAMPLE.as_bytes(), 5)?, None);
         Ok(())
     }
 }
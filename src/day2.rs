@@ -1,15 +1,67 @@
-use std::cmp::Ordering;
+use std::fmt::{self, Display};
 
 use duplicate::duplicate_item;
-use stable_eyre::{
-    eyre::{bail, ensure},
-    Report,
+use stable_eyre::{eyre::bail, Report};
+
+use crate::{Answer, Solution};
+
+mod cipher;
+mod crack;
+mod fast;
+mod parse;
+mod predict;
+mod report;
+mod rules;
+mod solver;
+mod stream;
+mod tournament;
+
+pub use cipher::Cipher;
+pub use crack::{crack, Interpretation, Ranking, Reading};
+pub use fast::{part1_bytes, part1_parsed, part2_bytes, part2_parsed, ScoreTable};
+pub use predict::{
+    compare, play, predictors, FrequencyPredictor, MarkovPredictor, PatternPredictor, Predictor,
+};
+pub use report::{report_part1, report_part2, MatchReport, ReportFormat, RoundReport};
+pub use rules::{
+    GameRules, OutcomeRule, OutcomeRules, Shape, ShapeRule, ROCK_PAPER_SCISSORS,
+    ROCK_PAPER_SCISSORS_LIZARD_SPOCK,
+};
+pub use solver::{solve, Plan, Target};
+pub use stream::{part1_parallel, part1_reader, part2_parallel, part2_reader};
+pub use tournament::{
+    AdaptiveStrategy, Entrant, FixedStrategy, MatchResult, Pairing, RandomStrategy, Standing,
+    Strategy, Tournament, TournamentResult,
 };
-use strum::{EnumIter, IntoEnumIterator};
 
 /// The puzzle input.
 pub const INPUT: &str = include_str!("input/day2");
 
+/// The solution for day 2.
+pub struct Day2;
+
+impl Solution for Day2 {
+    fn day(&self) -> u8 {
+        2
+    }
+
+    fn title(&self) -> &'static str {
+        "Rock Paper Scissors"
+    }
+
+    fn input(&self) -> &'static str {
+        INPUT
+    }
+
+    fn part1(&self, input: &str) -> Result<Answer, Report> {
+        part1(input).map(Answer::from)
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, Report> {
+        part2(input).map(Answer::from)
+    }
+}
+
 /// one Elf gives you an encrypted strategy guide (your puzzle input) that they say will be sure to help you win. "The
 /// first column is what your opponent is going to play: A for Rock, B for Paper, and C for Scissors. The second
 /// column--" Suddenly, the Elf is called away to help with someone's tent.
@@ -42,7 +94,12 @@ pub const INPUT: &str = include_str!("input/day2");
 ///
 /// In this example, if you were to follow the strategy guide, you would get a total score of 15 (8 + 1 + 6).
 pub fn part1(input: &str) -> Result<usize, Report> {
-    parse_rounds(input, parse_round).map(score_rounds)
+    part1_with(input, &ROCK_PAPER_SCISSORS)
+}
+
+/// Solve [`part1`] for a game played with different rules.
+pub fn part1_with(input: &str, rules: &GameRules) -> Result<usize, Report> {
+    part1_bytes(input.as_bytes(), rules)
 }
 
 /// "Anyway, the second column says how the round needs to end: X means you need to lose, Y means you need to end the
@@ -58,208 +115,77 @@ pub fn part1(input: &str) -> Result<usize, Report> {
 ///
 /// Now that you're correctly decrypting the ultra top secret strategy guide, you would get a total score of 12.
 pub fn part2(input: &str) -> Result<usize, Report> {
-    parse_rounds(input, parse_constraint)
-        .and_then(reconstruct_rounds)
-        .map(score_rounds)
-}
-fn parse_rounds<T, F>(input: &str, parser: F) -> Result<Vec<(OpponentMove, T)>, Report>
-where
-    F: Fn(&str) -> Result<(OpponentMove, T), Report>,
-{
-    input.lines().map(parser).collect()
+    part2_with(input, &ROCK_PAPER_SCISSORS)
 }
 
-fn score_rounds(rounds: Vec<(OpponentMove, PlayerMove)>) -> usize {
-    rounds
-        .into_iter()
-        .map(|(opponent, player)| round_score(opponent, player))
-        .sum()
+/// Solve [`part2`] for a game played with different rules.
+pub fn part2_with(input: &str, rules: &GameRules) -> Result<usize, Report> {
+    part2_bytes(input.as_bytes(), rules)
 }
 
+/// Both players throw shapes from the same game, the aliases just make it clear whose move is whose.
+pub type OpponentMove = Shape;
+
+/// Both players throw shapes from the same game, the aliases just make it clear whose move is whose.
+pub type PlayerMove = Shape;
+
 fn reconstruct_rounds(
+    rules: &GameRules,
     rounds: Vec<(OpponentMove, PlayerConstraint)>,
 ) -> Result<Vec<(OpponentMove, PlayerMove)>, Report> {
     rounds
         .into_iter()
         .map(|(opponent, constraint)| {
-            let player_move = desired_move(opponent, constraint)?;
+            let player_move = desired_move(rules, opponent, constraint)?;
             Ok((opponent, player_move))
         })
         .collect()
 }
 
 /// Calculate the move the player should make given the desired end state for the round.
+///
+/// If more than one move would end the round that way, the one worth the most points is chosen.
 fn desired_move(
+    rules: &GameRules,
     opponent: OpponentMove,
     constraint: PlayerConstraint,
 ) -> Result<PlayerMove, Report> {
     // To keep things simple, just brute force it.
-    for possible_move in PlayerMove::iter() {
-        if evaluate_round(opponent, possible_move) == constraint {
-            return Ok(possible_move);
-        }
+    let possible_move = rules
+        .shapes()
+        .filter(|possible_move| evaluate_round(rules, opponent, *possible_move) == constraint)
+        .max_by_key(|possible_move| rules.score(*possible_move));
+
+    match possible_move {
+        Some(possible_move) => Ok(possible_move),
+        None => bail!("no possible move found that satisfies player move constraint {constraint:?} for opponent move {opponent:?}"),
     }
-
-    bail!("no possible move found that satisfies player move constraint {constraint:?} for opponent move {opponent:?}");
 }
 
 /// Evaluate who won a round.
-fn evaluate_round(opponent: OpponentMove, player: PlayerMove) -> Round {
-    let Some(cmp) = PartialOrd::partial_cmp(&opponent, &player) else { unreachable!() };
-    match cmp {
-        Ordering::Less => Round::PlayerWin,
-        Ordering::Equal => Round::Draw,
-        Ordering::Greater => Round::PlayerLose,
-    }
-}
-
-fn parse_round(input: &str) -> Result<(OpponentMove, PlayerMove), Report> {
-    // Do it the hacky way, I don't feel like figuring out nom right now
-    ensure!(
-        input.len() == 3,
-        "inputs must consist of 3 characters, got '{input}'"
-    );
-
-    // Just assume 3 chars, separated by space.
-    let mut chars = input.chars();
-    let opponent = OpponentMove::parse(chars.next())?;
-    chars.next();
-    let player = PlayerMove::parse(chars.next())?;
-
-    Ok((opponent, player))
-}
-
-fn parse_constraint(input: &str) -> Result<(OpponentMove, PlayerConstraint), Report> {
-    // Do it the hacky way, I don't feel like figuring out nom right now
-    ensure!(
-        input.len() == 3,
-        "inputs must consist of 3 characters, got '{input}'"
-    );
-
-    // Just assume 3 chars, separated by space.
-    let mut chars = input.chars();
-    let opponent = OpponentMove::parse(chars.next())?;
-    chars.next();
-    let player = PlayerConstraint::parse(chars.next())?;
-
-    Ok((opponent, player))
+fn evaluate_round(rules: &GameRules, opponent: OpponentMove, player: PlayerMove) -> Round {
+    rules.evaluate(opponent, player)
 }
 
-fn round_score(opponent: OpponentMove, player: PlayerMove) -> usize {
-    evaluate_round(opponent, player).score() + player.score()
-}
-
-trait Score {
-    fn score(&self) -> usize;
+fn round_score(rules: &GameRules, opponent: OpponentMove, player: PlayerMove) -> usize {
+    rules.outcome_score(evaluate_round(rules, opponent, player)) + rules.score(player)
 }
 
 /// The result of a single round.
-enum Round {
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Round {
     PlayerLose,
     Draw,
     PlayerWin,
 }
 
-impl Score for Round {
-    fn score(&self) -> usize {
-        match self {
-            Round::PlayerLose => 0,
-            Round::Draw => 3,
-            Round::PlayerWin => 6,
-        }
-    }
-}
-
-/// The moves an opponent may take.
-#[derive(Debug, Copy, Clone)]
-enum OpponentMove {
-    Rock,
-    Paper,
-    Scissors,
-}
-
-impl OpponentMove {
-    fn parse(input: Option<char>) -> Result<Self, Report> {
-        match input {
-            Some('A') => Ok(OpponentMove::Rock),
-            Some('B') => Ok(OpponentMove::Paper),
-            Some('C') => Ok(OpponentMove::Scissors),
-            None => bail!("unexpected end of input"),
-            _ => bail!("unexpected input"),
-        }
-    }
-}
-
-/// The moves the player may take.
-#[derive(Copy, Clone, EnumIter)]
-enum PlayerMove {
-    Rock,
-    Paper,
-    Scissors,
-}
-
-impl Score for PlayerMove {
-    fn score(&self) -> usize {
+impl Display for Round {
+    /// Rounds are described from the player's point of view: `lose`, `draw` or `win`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            PlayerMove::Rock => 1,
-            PlayerMove::Paper => 2,
-            PlayerMove::Scissors => 3,
-        }
-    }
-}
-
-impl PlayerMove {
-    fn parse(input: Option<char>) -> Result<Self, Report> {
-        match input {
-            Some('X') => Ok(PlayerMove::Rock),
-            Some('Y') => Ok(PlayerMove::Paper),
-            Some('Z') => Ok(PlayerMove::Scissors),
-            None => bail!("unexpected end of input"),
-            _ => bail!("unexpected input"),
-        }
-    }
-}
-
-/// `OpponentMove` and `PlayerMove` are semantically equivalent, let's make them comparable.
-#[duplicate_item(
-    local target;
-    [ OpponentMove ] [ PlayerMove ];
-    [ PlayerMove ] [ OpponentMove ];
-)]
-impl PartialEq<target> for local {
-    fn eq(&self, other: &target) -> bool {
-        match self {
-            local::Rock => matches!(other, target::Rock),
-            local::Paper => matches!(other, target::Paper),
-            local::Scissors => matches!(other, target::Scissors),
-        }
-    }
-}
-
-/// `OpponentMove` and `PlayerMove` are semantically equivalent, let's make them orderable.
-#[duplicate_item(
-    local target;
-    [ OpponentMove ] [ PlayerMove ];
-    [ PlayerMove ] [ OpponentMove ];
-)]
-impl PartialOrd<target> for local {
-    fn partial_cmp(&self, other: &target) -> Option<Ordering> {
-        match self {
-            local::Rock => match other {
-                target::Rock => Some(Ordering::Equal),
-                target::Paper => Some(Ordering::Less),
-                target::Scissors => Some(Ordering::Greater),
-            },
-            local::Paper => match other {
-                target::Rock => Some(Ordering::Greater),
-                target::Paper => Some(Ordering::Equal),
-                target::Scissors => Some(Ordering::Less),
-            },
-            local::Scissors => match other {
-                target::Rock => Some(Ordering::Less),
-                target::Paper => Some(Ordering::Greater),
-                target::Scissors => Some(Ordering::Equal),
-            },
+            Round::PlayerLose => write!(f, "lose"),
+            Round::Draw => write!(f, "draw"),
+            Round::PlayerWin => write!(f, "win"),
         }
     }
 }
@@ -272,14 +198,12 @@ enum PlayerConstraint {
     PlayerLose,
 }
 
-impl PlayerConstraint {
-    fn parse(input: Option<char>) -> Result<Self, Report> {
-        match input {
-            Some('X') => Ok(PlayerConstraint::PlayerLose),
-            Some('Y') => Ok(PlayerConstraint::Draw),
-            Some('Z') => Ok(PlayerConstraint::PlayerWin),
-            None => bail!("unexpected end of input"),
-            _ => bail!("unexpected input"),
+impl From<Round> for PlayerConstraint {
+    fn from(round: Round) -> Self {
+        match round {
+            Round::PlayerLose => PlayerConstraint::PlayerLose,
+            Round::Draw => PlayerConstraint::Draw,
+            Round::PlayerWin => PlayerConstraint::PlayerWin,
         }
     }
 }
@@ -303,6 +227,7 @@ impl PartialEq<target> for local {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ParseError;
 
     #[test]
     fn test_part1() -> Result<(), Report> {
@@ -315,4 +240,77 @@ mod tests {
         assert_eq!(part2(INPUT)?, 13600);
         Ok(())
     }
+
+    #[test]
+    fn parse_error_position() {
+        let err = part1("A Y\nB X\nC Q\n").expect_err("must error");
+        let err = err
+            .downcast_ref::<ParseError>()
+            .expect("must be a parse error");
+        assert_eq!(err.line_number(), 3);
+        assert_eq!(err.column(), 3);
+        assert_eq!(err.text(), "Q");
+
+        let err = part2("A Y\nD Z\n").expect_err("must error");
+        let err = err
+            .downcast_ref::<ParseError>()
+            .expect("must be a parse error");
+        assert_eq!(err.line_number(), 2);
+        assert_eq!(err.column(), 1);
+        assert_eq!(err.text(), "D");
+
+        let err = part1("A Y\nAY\n").expect_err("must error");
+        let err = err
+            .downcast_ref::<ParseError>()
+            .expect("must be a parse error");
+        assert_eq!(err.line_number(), 2);
+        assert_eq!(err.column(), 2);
+        assert_eq!(err.message(), "expected whitespace between the columns");
+        assert_eq!(err.text(), "Y");
+
+        let err = part1("A Y Z\n").expect_err("must error");
+        let err = err
+            .downcast_ref::<ParseError>()
+            .expect("must be a parse error");
+        assert_eq!(err.column(), 5);
+        assert_eq!(err.message(), "expected the end of the line or a comment");
+
+        let err = part2("  B\n").expect_err("must error");
+        let err = err
+            .downcast_ref::<ParseError>()
+            .expect("must be a parse error");
+        assert_eq!(err.column(), 4);
+        assert_eq!(err.message(), "expected whitespace between the columns");
+    }
+
+    #[test]
+    fn lizard_spock() -> Result<(), Report> {
+        // Spock beats Rock (5 + 6), Rock beats Lizard (1 + 6), Lizard beats Spock (4 + 6), Scissors beats Paper (3 + 6).
+        let rules = &*ROCK_PAPER_SCISSORS_LIZARD_SPOCK;
+        let guide = "A W\nD X\nE V\nB Z\n";
+        assert_eq!(part1_with(guide, rules)?, 37);
+
+        // When more than one shape ends the round as required, the one worth the most is chosen:
+        // Spock beats Rock (5 + 6), Lizard draws with Lizard (4 + 3), Scissors loses to Spock (3 + 0).
+        let guide = "A Z\nD Y\nE X\n";
+        assert_eq!(part2_with(guide, rules)?, 21);
+
+        let err = part1_with("A Q\n", rules).expect_err("must error");
+        let err = err
+            .downcast_ref::<ParseError>()
+            .expect("must be a parse error");
+        assert_eq!(
+            err.message(),
+            "expected player move 'X', 'Y', 'Z', 'V' or 'W'"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_flexible_guide() -> Result<(), Report> {
+        let guide = "# the elf's guide\r\n  A \t Y\r\n\r\nB X   # lose this one\r\nC\tZ\n\n\n  \n";
+        assert_eq!(part1(guide)?, 15);
+        assert_eq!(part2(guide)?, 12);
+        Ok(())
+    }
 }
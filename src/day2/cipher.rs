@@ -0,0 +1,185 @@
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+use stable_eyre::{
+    eyre::{bail, Context},
+    Report,
+};
+
+use super::{GameRules, OutcomeRules, ShapeRule, ROCK_PAPER_SCISSORS};
+
+/// How to read an "encrypted" strategy guide: which symbols mean which shapes or outcomes,
+/// which shape beats which, and what each shape and outcome is worth.
+///
+/// A cipher can be built in code, or loaded from TOML or JSON with the same structure, e.g.:
+/// ```toml
+/// beats = [["Rock", "Scissors"], ["Scissors", "Paper"], ["Paper", "Rock"]]
+///
+/// [[shapes]]
+/// name = "Rock"
+/// score = 1
+/// opponent_symbol = "A"
+/// player_symbol = "X"
+///
+/// # ... and so on for Paper and Scissors.
+///
+/// [outcomes]
+/// lose = { symbol = "X", score = 0 }
+/// draw = { symbol = "Y", score = 3 }
+/// win = { symbol = "Z", score = 6 }
+/// ```
+///
+/// `outcomes` may be omitted, in which case X, Y and Z mean lose, draw and win for 0, 3 and 6 points.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cipher {
+    /// The shapes in the game.
+    pub shapes: Vec<ShapeRule>,
+
+    /// Which shape beats which, as `(winner, loser)` pairs of names.
+    pub beats: Vec<(String, String)>,
+
+    /// The symbols and scores for the outcome of a round.
+    #[serde(default)]
+    pub outcomes: OutcomeRules,
+}
+
+impl Cipher {
+    /// Parse a cipher from TOML.
+    pub fn from_toml(input: &str) -> Result<Self, Report> {
+        toml::from_str(input).wrap_err("parse cipher as toml")
+    }
+
+    /// Parse a cipher from JSON.
+    pub fn from_json(input: &str) -> Result<Self, Report> {
+        serde_json::from_str(input).wrap_err("parse cipher as json")
+    }
+
+    /// Load a cipher from a `.toml` or `.json` file.
+    pub fn load(path: &Path) -> Result<Self, Report> {
+        let input =
+            fs::read_to_string(path).wrap_err_with(|| format!("read cipher from {path:?}"))?;
+        let cipher = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => Self::from_toml(&input),
+            Some("json") => Self::from_json(&input),
+            _ => bail!("cipher {path:?} must be a .toml or .json file"),
+        };
+        cipher.wrap_err_with(|| format!("load cipher from {path:?}"))
+    }
+
+    /// Build the rules of the game described by the cipher, validating it along the way.
+    pub fn rules(&self) -> Result<GameRules, Report> {
+        let beats = self
+            .beats
+            .iter()
+            .map(|(winner, loser)| (winner.as_str(), loser.as_str()))
+            .collect::<Vec<_>>();
+        GameRules::new(self.shapes.clone(), &beats)?.with_outcomes(self.outcomes)
+    }
+}
+
+impl Default for Cipher {
+    /// The cipher the elf most likely meant: [`ROCK_PAPER_SCISSORS`].
+    fn default() -> Self {
+        Self::from(&*ROCK_PAPER_SCISSORS)
+    }
+}
+
+impl From<&GameRules> for Cipher {
+    fn from(rules: &GameRules) -> Self {
+        let name = |shape| rules.rule(shape).name.clone();
+        Self {
+            shapes: rules
+                .shapes()
+                .map(|shape| rules.rule(shape).clone())
+                .collect(),
+            beats: rules
+                .beats()
+                .map(|(winner, loser)| (name(winner), name(loser)))
+                .collect(),
+            outcomes: *rules.outcomes(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day2::{part1_with, part2_with, OutcomeRule, Round, Shape, INPUT};
+
+    const CLASSIC: &str = r#"
+        beats = [["Rock", "Scissors"], ["Paper", "Rock"], ["Scissors", "Paper"]]
+
+        [[shapes]]
+        name = "Rock"
+        score = 1
+        opponent_symbol = "A"
+        player_symbol = "X"
+
+        [[shapes]]
+        name = "Paper"
+        score = 2
+        opponent_symbol = "B"
+        player_symbol = "Y"
+
+        [[shapes]]
+        name = "Scissors"
+        score = 3
+        opponent_symbol = "C"
+        player_symbol = "Z"
+    "#;
+
+    #[test]
+    fn load_toml() -> Result<(), Report> {
+        let cipher = Cipher::from_toml(CLASSIC)?;
+        assert_eq!(cipher, Cipher::default());
+
+        let rules = cipher.rules()?;
+        assert_eq!(part1_with(INPUT, &rules)?, 11386);
+        assert_eq!(part2_with(INPUT, &rules)?, 13600);
+        Ok(())
+    }
+
+    #[test]
+    fn load_json() -> Result<(), Report> {
+        let json = serde_json::to_string(&Cipher::default())?;
+        assert_eq!(Cipher::from_json(&json)?, Cipher::default());
+        Ok(())
+    }
+
+    #[test]
+    fn alternate_cipher() -> Result<(), Report> {
+        // Read the second column backwards, and make losing worth more than winning.
+        let mut cipher = Cipher::default();
+        for (shape, symbol) in cipher.shapes.iter_mut().zip(['Z', 'Y', 'X']) {
+            shape.player_symbol = symbol;
+        }
+        cipher.outcomes = OutcomeRules {
+            lose: OutcomeRule::new('Z', 6),
+            draw: OutcomeRule::new('Y', 3),
+            win: OutcomeRule::new('X', 0),
+        };
+        let rules = cipher.rules()?;
+        assert_eq!(rules.by_player_symbol('X'), Some(Shape::SCISSORS));
+        assert_eq!(rules.by_outcome_symbol('X'), Some(Round::PlayerWin));
+
+        // Scissors loses to Rock (3 + 6), Scissors beats Paper (3 + 0), Scissors draws with Scissors (3 + 3).
+        assert_eq!(part1_with("A X\nB X\nC X\n", &rules)?, 9 + 3 + 6);
+
+        // Paper beats Rock (2 + 0), Rock loses to Paper (1 + 6), Scissors draws with Scissors (3 + 3).
+        assert_eq!(part2_with("A X\nB Z\nC Y\n", &rules)?, 2 + 7 + 6);
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_cipher() {
+        assert!(Cipher::from_toml("shapes = []").is_err());
+
+        let mut cipher = Cipher::default();
+        cipher.beats.pop();
+        assert!(cipher.rules().is_err());
+
+        let mut cipher = Cipher::default();
+        cipher.outcomes.win.symbol = 'X';
+        assert!(cipher.rules().is_err());
+    }
+}
@@ -0,0 +1,346 @@
+use std::{cmp::Reverse, fmt::Write, str::FromStr};
+
+use itertools::Itertools;
+use stable_eyre::{
+    eyre::{bail, Context},
+    Report,
+};
+
+use super::{
+    desired_move, evaluate_round, parse::any_symbol, parse::parse_rounds, round_score, GameRules,
+    OpponentMove, PlayerMove, Round, Shape,
+};
+
+/// One way of reading the second column of the strategy guide.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reading {
+    /// Each symbol is the move the player should make.
+    Moves(Vec<(char, Shape)>),
+
+    /// Each symbol is how the round needs to end.
+    Outcomes(Vec<(char, Round)>),
+}
+
+impl Reading {
+    /// Describe the reading using the names from the rules, e.g. `moves X=Rock, Y=Paper, Z=Scissors`.
+    pub fn describe(&self, rules: &GameRules) -> String {
+        let mut description = String::new();
+        match self {
+            Reading::Moves(mapping) => {
+                description.push_str("moves");
+                for (index, (symbol, shape)) in mapping.iter().enumerate() {
+                    let separator = if index == 0 { " " } else { ", " };
+                    let name = &rules.rule(*shape).name;
+                    write!(description, "{separator}{symbol}={name}").expect("write to string");
+                }
+            }
+            Reading::Outcomes(mapping) => {
+                description.push_str("outcomes");
+                for (index, (symbol, outcome)) in mapping.iter().enumerate() {
+                    let separator = if index == 0 { " " } else { ", " };
+                    write!(description, "{separator}{symbol}={outcome}").expect("write to string");
+                }
+            }
+        }
+        description
+    }
+}
+
+/// The result of following the strategy guide under one [`Reading`] of its second column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interpretation {
+    /// How the second column was read.
+    pub reading: Reading,
+
+    /// The player's total score.
+    pub score: usize,
+
+    /// The opponent's total score, earned the same way as the player's.
+    pub opponent_score: usize,
+
+    /// The number of rounds the player won.
+    pub wins: usize,
+
+    /// The number of rounds that were drawn.
+    pub draws: usize,
+
+    /// The number of rounds the player lost.
+    pub losses: usize,
+}
+
+impl Interpretation {
+    /// How many points the player finishes ahead of the opponent, negative if they finish behind.
+    pub fn margin(&self) -> i64 {
+        self.score as i64 - self.opponent_score as i64
+    }
+}
+
+/// How to order interpretations, from most to least plausible.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Ranking {
+    /// The highest score first.
+    HighestScore,
+
+    /// The score closest to the given score first.
+    ClosestTo(usize),
+
+    /// Winning every time would be suspicious, but the guide is supposed to help win the tournament:
+    /// interpretations where the player outscores the opponent by the narrowest margin come first,
+    /// followed by the rest from the smallest deficit to the largest.
+    NarrowestWin,
+}
+
+impl Ranking {
+    /// Sort the interpretations from most to least plausible.
+    /// Interpretations that rank equally keep their order.
+    pub fn sort(self, interpretations: &mut [Interpretation]) {
+        match self {
+            Ranking::HighestScore => {
+                interpretations.sort_by_key(|interpretation| Reverse(interpretation.score))
+            }
+            Ranking::ClosestTo(target) => {
+                interpretations.sort_by_key(|interpretation| interpretation.score.abs_diff(target))
+            }
+            Ranking::NarrowestWin => interpretations.sort_by_key(|interpretation| {
+                let margin = interpretation.margin();
+                (margin <= 0, margin.unsigned_abs())
+            }),
+        }
+    }
+}
+
+impl FromStr for Ranking {
+    type Err = Report;
+
+    /// Rankings are given as `highest`, `narrowest-win`, or `closest=N` for the score closest to `N`.
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "highest" => Ok(Ranking::HighestScore),
+            "narrowest-win" => Ok(Ranking::NarrowestWin),
+            _ => match input.strip_prefix("closest=") {
+                Some(score) => score
+                    .parse()
+                    .map(Ranking::ClosestTo)
+                    .wrap_err_with(|| format!("parse score '{score}'")),
+                None => bail!("expected 'highest', 'narrowest-win' or 'closest=N', got '{input}'"),
+            },
+        }
+    }
+}
+
+/// The puzzle shows that the first reading of the second column (as moves) was wrong,
+/// and that it really says how each round needs to end.
+///
+/// Try every way of reading the second column, ranked by `ranking`:
+/// every assignment of the player's symbols in `rules` to shapes,
+/// and every assignment of the outcome symbols in `rules` to outcomes.
+/// Readings that leave a symbol in the guide without a meaning, or that call for an outcome
+/// no move can reach, are left out; it's only an error if no reading is left.
+pub fn crack(
+    input: &str,
+    rules: &GameRules,
+    ranking: Ranking,
+) -> Result<Vec<Interpretation>, Report> {
+    let rounds = parse_rounds(input, rules, any_symbol)?;
+
+    let player_symbols = rules
+        .shapes()
+        .map(|shape| rules.rule(shape).player_symbol)
+        .collect::<Vec<_>>();
+    let outcome_symbols = rules
+        .outcomes()
+        .iter()
+        .map(|(_, rule)| rule.symbol)
+        .collect::<Vec<_>>();
+    let outcomes = rules
+        .outcomes()
+        .iter()
+        .map(|(outcome, _)| outcome)
+        .collect::<Vec<_>>();
+
+    let move_readings = player_symbols
+        .iter()
+        .copied()
+        .permutations(player_symbols.len())
+        .map(|symbols| Reading::Moves(symbols.into_iter().zip(rules.shapes()).collect()));
+    let outcome_readings = outcome_symbols
+        .iter()
+        .copied()
+        .permutations(outcome_symbols.len())
+        .map(|symbols| {
+            Reading::Outcomes(symbols.into_iter().zip(outcomes.iter().copied()).collect())
+        });
+
+    let mut interpretations = move_readings
+        .chain(outcome_readings)
+        .filter_map(|reading| interpret(rules, &rounds, reading))
+        .collect::<Vec<_>>();
+    if interpretations.is_empty() {
+        bail!("no reading of the second column gives every round a move");
+    }
+    ranking.sort(&mut interpretations);
+    Ok(interpretations)
+}
+
+/// Follow the strategy guide, reading the second column of each round with `reading`,
+/// or `None` if the reading doesn't give some round a move.
+fn interpret(
+    rules: &GameRules,
+    rounds: &[(OpponentMove, char)],
+    reading: Reading,
+) -> Option<Interpretation> {
+    let mut interpretation = Interpretation {
+        reading,
+        score: 0,
+        opponent_score: 0,
+        wins: 0,
+        draws: 0,
+        losses: 0,
+    };
+
+    for (opponent, symbol) in rounds {
+        let player = player_move(rules, &interpretation.reading, *opponent, *symbol)?;
+
+        interpretation.score += round_score(rules, *opponent, player);
+        interpretation.opponent_score += round_score(rules, player, *opponent);
+        match evaluate_round(rules, *opponent, player) {
+            Round::PlayerWin => interpretation.wins += 1,
+            Round::Draw => interpretation.draws += 1,
+            Round::PlayerLose => interpretation.losses += 1,
+        }
+    }
+
+    Some(interpretation)
+}
+
+/// The move the player makes against the opponent, reading `symbol` with `reading`,
+/// or `None` if the symbol has no meaning in the reading or no move ends the round as it says.
+fn player_move(
+    rules: &GameRules,
+    reading: &Reading,
+    opponent: OpponentMove,
+    symbol: char,
+) -> Option<PlayerMove> {
+    match reading {
+        Reading::Moves(mapping) => mapping
+            .iter()
+            .find(|(candidate, _)| *candidate == symbol)
+            .map(|(_, shape)| *shape),
+        Reading::Outcomes(mapping) => mapping
+            .iter()
+            .find(|(candidate, _)| *candidate == symbol)
+            .and_then(|(_, outcome)| desired_move(rules, opponent, (*outcome).into()).ok()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day2::{part1, part2, INPUT, ROCK_PAPER_SCISSORS, ROCK_PAPER_SCISSORS_LIZARD_SPOCK};
+
+    const EXAMPLE: &str = "A Y\nB X\nC Z\n";
+
+    #[test]
+    fn crack_example() -> Result<(), Report> {
+        let rules = &*ROCK_PAPER_SCISSORS;
+        let interpretations = crack(EXAMPLE, rules, Ranking::HighestScore)?;
+        assert_eq!(interpretations.len(), 12);
+
+        // Only reading the column as moves can win every round, for 8 + 9 + 7.
+        let best = &interpretations[0];
+        assert_eq!(
+            best.reading.describe(rules),
+            "moves Z=Rock, Y=Paper, X=Scissors"
+        );
+        assert_eq!(best.score, 24);
+        assert_eq!(best.wins, 3);
+        assert_eq!(best.opponent_score, 6);
+
+        // Both readings from the puzzle are among the interpretations.
+        let scores = interpretations
+            .iter()
+            .map(|interpretation| (interpretation.reading.describe(rules), interpretation.score))
+            .collect::<Vec<_>>();
+        assert!(scores.contains(&("moves X=Rock, Y=Paper, Z=Scissors".to_owned(), 15)));
+        assert!(scores.contains(&("outcomes X=lose, Y=draw, Z=win".to_owned(), 12)));
+        Ok(())
+    }
+
+    #[test]
+    fn rankings() -> Result<(), Report> {
+        let rules = &*ROCK_PAPER_SCISSORS;
+        let closest = crack(EXAMPLE, rules, Ranking::ClosestTo(12))?;
+        assert_eq!(closest[0].score, 12);
+
+        let narrowest = crack(EXAMPLE, rules, Ranking::NarrowestWin)?;
+        let smallest_win = narrowest
+            .iter()
+            .map(Interpretation::margin)
+            .filter(|margin| *margin > 0)
+            .min();
+        assert_eq!(Some(narrowest[0].margin()), smallest_win);
+        assert!(narrowest
+            .last()
+            .is_some_and(|interpretation| interpretation.margin() <= 0));
+        Ok(())
+    }
+
+    #[test]
+    fn crack_input() -> Result<(), Report> {
+        let rules = &*ROCK_PAPER_SCISSORS;
+        let interpretations = crack(INPUT, rules, Ranking::HighestScore)?;
+        let find = |description: &str| {
+            interpretations
+                .iter()
+                .find(|interpretation| interpretation.reading.describe(rules) == description)
+                .map(|interpretation| interpretation.score)
+        };
+        assert_eq!(
+            find("moves X=Rock, Y=Paper, Z=Scissors"),
+            Some(part1(INPUT)?)
+        );
+        assert_eq!(find("outcomes X=lose, Y=draw, Z=win"), Some(part2(INPUT)?));
+        Ok(())
+    }
+
+    #[test]
+    fn crack_lizard_spock() -> Result<(), Report> {
+        let interpretations = crack(
+            "A X\n",
+            &ROCK_PAPER_SCISSORS_LIZARD_SPOCK,
+            Ranking::HighestScore,
+        )?;
+        assert_eq!(interpretations.len(), 5 * 4 * 3 * 2 + 6);
+
+        // 'W' isn't an outcome symbol, so only the readings as moves are left.
+        let interpretations = crack(
+            "A Z\nB W\n",
+            &ROCK_PAPER_SCISSORS_LIZARD_SPOCK,
+            Ranking::HighestScore,
+        )?;
+        assert_eq!(interpretations.len(), 5 * 4 * 3 * 2);
+        assert!(interpretations
+            .iter()
+            .all(|interpretation| matches!(interpretation.reading, Reading::Moves(_))));
+
+        // 'Q' has no meaning in any reading.
+        let err = crack(
+            "A Q\n",
+            &ROCK_PAPER_SCISSORS_LIZARD_SPOCK,
+            Ranking::HighestScore,
+        )
+        .expect_err("must error");
+        assert!(format!("{err:?}").contains("no reading of the second column"));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_ranking() -> Result<(), Report> {
+        assert_eq!(Ranking::from_str("highest")?, Ranking::HighestScore);
+        assert_eq!(Ranking::from_str("narrowest-win")?, Ranking::NarrowestWin);
+        assert_eq!(Ranking::from_str("closest=12")?, Ranking::ClosestTo(12));
+        assert!(Ranking::from_str("closest=twelve").is_err());
+        assert!(Ranking::from_str("lowest").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,325 @@
+use std::str;
+
+use stable_eyre::{
+    eyre::{ensure, Context},
+    Report,
+};
+
+use super::{
+    desired_move,
+    parse::{parse_rounds, player_constraint, player_move},
+    reconstruct_rounds, round_score,
+    stream::{score_move, score_outcome},
+    GameRules, OpponentMove, PlayerMove,
+};
+
+/// Marks a symbol that doesn't stand for anything in the lookup tables.
+const NONE: u8 = u8::MAX;
+
+/// The score of every possible round, worked out once up front so that scoring a round is a lookup.
+///
+/// Symbols are looked up by their byte, so only ASCII symbols are covered;
+/// rounds using anything else have to go through the full parser.
+/// So do rounds using whitespace or `#` as a symbol, since the parser reads those as part of the layout or a comment.
+#[derive(Debug, Clone)]
+pub struct ScoreTable {
+    shapes: usize,
+
+    /// The index of the shape or outcome each byte stands for, or [`NONE`].
+    opponent_symbols: [u8; 256],
+    player_symbols: [u8; 256],
+    outcome_symbols: [u8; 256],
+
+    /// The score when the opponent throws `o` and the player throws `p` is at `o * shapes + p`.
+    move_scores: Vec<usize>,
+
+    /// The score when the opponent throws `o` and the round needs to end with outcome `r` is at `o * 3 + r`,
+    /// or `None` if no move ends the round that way.
+    outcome_scores: Vec<Option<usize>>,
+}
+
+impl ScoreTable {
+    pub fn new(rules: &GameRules) -> Result<Self, Report> {
+        let shapes = rules.len();
+        ensure!(
+            shapes < usize::from(NONE),
+            "lookup tables support at most {} shapes",
+            NONE - 1
+        );
+
+        let mut table = Self {
+            shapes,
+            opponent_symbols: [NONE; 256],
+            player_symbols: [NONE; 256],
+            outcome_symbols: [NONE; 256],
+            move_scores: Vec::with_capacity(shapes * shapes),
+            outcome_scores: Vec::with_capacity(shapes * 3),
+        };
+
+        for (index, shape) in (0..).zip(rules.shapes()) {
+            let rule = rules.rule(shape);
+            if let Some(symbol) = ascii(rule.opponent_symbol) {
+                table.opponent_symbols[symbol] = index;
+            }
+            if let Some(symbol) = ascii(rule.player_symbol) {
+                table.player_symbols[symbol] = index;
+            }
+        }
+        for (index, (_, rule)) in (0..).zip(rules.outcomes().iter()) {
+            if let Some(symbol) = ascii(rule.symbol) {
+                table.outcome_symbols[symbol] = index;
+            }
+        }
+
+        for opponent in rules.shapes() {
+            for player in rules.shapes() {
+                table.move_scores.push(round_score(rules, opponent, player));
+            }
+            for (outcome, _) in rules.outcomes().iter() {
+                let player = desired_move(rules, opponent, outcome.into()).ok();
+                let score = player.map(|player| round_score(rules, opponent, player));
+                table.outcome_scores.push(score);
+            }
+        }
+
+        Ok(table)
+    }
+
+    /// The score for a round as in [`part1`](super::part1), given the symbols in each column.
+    pub fn move_score(&self, opponent: u8, player: u8) -> Option<usize> {
+        let opponent = self.opponent_symbols[usize::from(opponent)];
+        let player = self.player_symbols[usize::from(player)];
+        if opponent == NONE || player == NONE {
+            return None;
+        }
+        Some(self.move_scores[usize::from(opponent) * self.shapes + usize::from(player)])
+    }
+
+    /// The score for a round as in [`part2`](super::part2), given the symbols in each column.
+    pub fn outcome_score(&self, opponent: u8, outcome: u8) -> Option<usize> {
+        let opponent = self.opponent_symbols[usize::from(opponent)];
+        let outcome = self.outcome_symbols[usize::from(outcome)];
+        if opponent == NONE || outcome == NONE {
+            return None;
+        }
+        self.outcome_scores[usize::from(opponent) * 3 + usize::from(outcome)]
+    }
+
+    /// Score line `number` of the guide as in [`part1`](super::part1).
+    ///
+    /// Lines in the usual format are looked up directly; anything else goes through the full parser,
+    /// which also reports errors.
+    pub(super) fn score_move_line(
+        &self,
+        rules: &GameRules,
+        number: usize,
+        line: &str,
+    ) -> Result<usize, Report> {
+        match strict_round(line.as_bytes())
+            .and_then(|(opponent, player)| self.move_score(opponent, player))
+        {
+            Some(score) => Ok(score),
+            None => score_move(rules, number, line),
+        }
+    }
+
+    /// Score line `number` of the guide as in [`part2`](super::part2).
+    ///
+    /// Lines in the usual format are looked up directly; anything else goes through the full parser,
+    /// which also reports errors.
+    pub(super) fn score_outcome_line(
+        &self,
+        rules: &GameRules,
+        number: usize,
+        line: &str,
+    ) -> Result<usize, Report> {
+        match strict_round(line.as_bytes())
+            .and_then(|(opponent, outcome)| self.outcome_score(opponent, outcome))
+        {
+            Some(score) => Ok(score),
+            None => score_outcome(rules, number, line),
+        }
+    }
+}
+
+/// Solve [`part1`](super::part1) for a game played with the given rules, working directly on the bytes of the guide.
+///
+/// Lines in the usual `A X` format are scored with a [`ScoreTable`], without any parsing.
+/// Lines in any other format go through the full parser, so the result is always the same as [`part1_reader`](super::part1_reader).
+pub fn part1_bytes(input: &[u8], rules: &GameRules) -> Result<usize, Report> {
+    let table = ScoreTable::new(rules)?;
+    score_bytes(
+        input,
+        |opponent, player| table.move_score(opponent, player),
+        |number, line| score_move(rules, number, line),
+    )
+}
+
+/// Solve [`part2`](super::part2) for a game played with the given rules, working directly on the bytes of the guide.
+///
+/// Lines in the usual `A X` format are scored with a [`ScoreTable`], without any parsing.
+/// Lines in any other format go through the full parser, so the result is always the same as [`part2_reader`](super::part2_reader).
+pub fn part2_bytes(input: &[u8], rules: &GameRules) -> Result<usize, Report> {
+    let table = ScoreTable::new(rules)?;
+    score_bytes(
+        input,
+        |opponent, outcome| table.outcome_score(opponent, outcome),
+        |number, line| score_outcome(rules, number, line),
+    )
+}
+
+/// Solve [`part1`](super::part1) for a game played with the given rules by parsing every round in full
+/// and working out its score from the rules, without a [`ScoreTable`].
+///
+/// This is how rounds were scored before the table, kept as a baseline for the benchmarks.
+pub fn part1_parsed(input: &str, rules: &GameRules) -> Result<usize, Report> {
+    let rounds = parse_rounds(input, rules, player_move(rules))?;
+    Ok(score_rounds(rules, rounds))
+}
+
+/// Solve [`part2`](super::part2) for a game played with the given rules by parsing every round in full,
+/// working out each move with [`desired_move`] and its score from the rules, without a [`ScoreTable`].
+///
+/// This is how rounds were scored before the table, kept as a baseline for the benchmarks.
+pub fn part2_parsed(input: &str, rules: &GameRules) -> Result<usize, Report> {
+    let rounds = parse_rounds(input, rules, player_constraint(rules))
+        .and_then(|rounds| reconstruct_rounds(rules, rounds))?;
+    Ok(score_rounds(rules, rounds))
+}
+
+fn score_rounds(rules: &GameRules, rounds: Vec<(OpponentMove, PlayerMove)>) -> usize {
+    rounds
+        .into_iter()
+        .map(|(opponent, player)| round_score(rules, opponent, player))
+        .sum()
+}
+
+/// Score every line of the guide, looking up lines in the usual format with `lookup`
+/// and falling back to `parse` for everything else.
+fn score_bytes<L, P>(input: &[u8], lookup: L, parse: P) -> Result<usize, Report>
+where
+    L: Fn(u8, u8) -> Option<usize>,
+    P: Fn(usize, &str) -> Result<usize, Report>,
+{
+    let mut total = 0;
+    for (index, line) in input.split(|byte| *byte == b'\n').enumerate() {
+        if let Some(score) = strict_round(line).and_then(|(first, second)| lookup(first, second)) {
+            total += score;
+            continue;
+        }
+
+        let number = index + 1;
+        let line = str::from_utf8(line).wrap_err_with(|| format!("read line {number}"))?;
+        total += parse(number, line.strip_suffix('\r').unwrap_or(line))?;
+    }
+    Ok(total)
+}
+
+/// The symbols in each column of a line in the usual format: two symbols separated by a single space.
+fn strict_round(line: &[u8]) -> Option<(u8, u8)> {
+    match line {
+        [first, b' ', second] | [first, b' ', second, b'\r'] => Some((*first, *second)),
+        _ => None,
+    }
+}
+
+/// The byte for the symbol, if it's ASCII and the parser would read it as a symbol.
+fn ascii(symbol: char) -> Option<usize> {
+    u8::try_from(symbol)
+        .ok()
+        .filter(|byte| byte.is_ascii() && !byte.is_ascii_whitespace() && *byte != b'#')
+        .map(usize::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        day2::{
+            part1_reader, part2_reader, ShapeRule, INPUT, ROCK_PAPER_SCISSORS,
+            ROCK_PAPER_SCISSORS_LIZARD_SPOCK,
+        },
+        ParseError,
+    };
+
+    #[test]
+    fn table() -> Result<(), Report> {
+        let table = ScoreTable::new(&ROCK_PAPER_SCISSORS)?;
+        assert_eq!(table.move_score(b'A', b'Y'), Some(8));
+        assert_eq!(table.move_score(b'B', b'X'), Some(1));
+        assert_eq!(table.outcome_score(b'A', b'Y'), Some(4));
+        assert_eq!(table.outcome_score(b'C', b'Z'), Some(7));
+        assert_eq!(table.move_score(b'D', b'X'), None);
+        assert_eq!(table.outcome_score(b'A', b'W'), None);
+        Ok(())
+    }
+
+    #[test]
+    fn bytes() -> Result<(), Report> {
+        let rules = &*ROCK_PAPER_SCISSORS;
+        assert_eq!(part1_bytes(INPUT.as_bytes(), rules)?, 11386);
+        assert_eq!(part2_bytes(INPUT.as_bytes(), rules)?, 13600);
+        assert_eq!(part1_parsed(INPUT, rules)?, 11386);
+        assert_eq!(part2_parsed(INPUT, rules)?, 13600);
+
+        // Lines that aren't in the usual format still work, they're just parsed properly.
+        let guide = "# the elf's guide\r\n  A \t Y\r\n\r\nB X   # lose this one\r\nC Z";
+        assert_eq!(part1_bytes(guide.as_bytes(), rules)?, 15);
+        assert_eq!(part2_bytes(guide.as_bytes(), rules)?, 12);
+
+        let rules = &*ROCK_PAPER_SCISSORS_LIZARD_SPOCK;
+        let guide = "A W\nD X\nE V\nB Z\n";
+        assert_eq!(
+            part1_bytes(guide.as_bytes(), rules)?,
+            part1_reader(guide.as_bytes(), rules)?
+        );
+        let guide = "A Z\nD Y\nE X\n";
+        assert_eq!(
+            part2_bytes(guide.as_bytes(), rules)?,
+            part2_reader(guide.as_bytes(), rules)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn layout_symbols() -> Result<(), Report> {
+        // Symbols the parser reads as a comment or whitespace aren't looked up,
+        // so the fast path agrees with the parser about them.
+        let rules = GameRules::new(
+            vec![
+                ShapeRule::new("Rock", 1, 'A', '#'),
+                ShapeRule::new("Paper", 2, '\t', 'Y'),
+                ShapeRule::new("Scissors", 3, 'C', 'Z'),
+            ],
+            &[
+                ("Rock", "Scissors"),
+                ("Paper", "Rock"),
+                ("Scissors", "Paper"),
+            ],
+        )?;
+        let table = ScoreTable::new(&rules)?;
+        assert_eq!(table.move_score(b'A', b'#'), None);
+        assert_eq!(table.move_score(b'\t', b'Y'), None);
+        assert_eq!(table.move_score(b'C', b'Z'), Some(6));
+
+        for guide in ["A #\nC Z\n", "\t Y\nC Z\n", "C Z\n# A #\n"] {
+            let fast = part1_bytes(guide.as_bytes(), &rules).map_err(|err| format!("{err}"));
+            let parsed = part1_reader(guide.as_bytes(), &rules).map_err(|err| format!("{err}"));
+            assert_eq!(fast, parsed, "{guide:?}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn bytes_error() {
+        let rules = &*ROCK_PAPER_SCISSORS;
+        let err = part1_bytes(b"A Y\nB X\nC Q\n", rules).expect_err("must error");
+        let err = err
+            .downcast_ref::<ParseError>()
+            .expect("must be a parse error");
+        assert_eq!(err.line_number(), 3);
+        assert_eq!(err.text(), "Q");
+
+        assert!(part1_bytes(b"A Y\n\xff Y\n", rules).is_err());
+    }
+}
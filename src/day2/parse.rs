@@ -0,0 +1,202 @@
+use nom::{
+    branch::alt,
+    bytes::complete::take_till1,
+    character::complete::{anychar, char, multispace0, satisfy, space1},
+    combinator::{cut, eof, map, map_opt, opt, rest},
+    error::{context, VerboseError, VerboseErrorKind},
+    sequence::{pair, preceded, terminated, tuple},
+    IResult,
+};
+use stable_eyre::Report;
+
+use super::{GameRules, OpponentMove, PlayerConstraint, PlayerMove};
+use crate::ParseError;
+
+/// The result of running a parser over part of a line of the strategy guide.
+pub(super) type ParseResult<'a, T> = IResult<&'a str, T, VerboseError<&'a str>>;
+
+/// Context for errors parsing the opponent's move.
+/// The symbols the rules accept are appended when the error is reported.
+const OPPONENT_MOVE: &str = "expected opponent move";
+
+/// Context for errors parsing the player's move.
+/// The symbols the rules accept are appended when the error is reported.
+const PLAYER_MOVE: &str = "expected player move";
+
+/// Context for errors parsing the outcome the player needs.
+/// The symbols the rules accept are appended when the error is reported.
+const OUTCOME: &str = "expected round outcome";
+
+/// Parse every round in the strategy guide, using `second_column` to parse the second column of each line.
+pub(super) fn parse_rounds<'a, T, F>(
+    input: &'a str,
+    rules: &'a GameRules,
+    second_column: F,
+) -> Result<Vec<(OpponentMove, T)>, Report>
+where
+    F: FnMut(&'a str) -> ParseResult<'a, T> + Copy,
+{
+    input
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| parse_line(index + 1, line, rules, second_column).transpose())
+        .map(|round| round.map_err(Report::from))
+        .collect()
+}
+
+/// Parse line `number` of the strategy guide, using `second_column` to parse the second column.
+///
+/// A round consists of the opponent's move and the second column, separated by any amount of whitespace.
+/// Anything after a `#` is a comment. Lines that are blank or only hold a comment don't contain a round.
+pub(super) fn parse_line<'a, T>(
+    number: usize,
+    line: &'a str,
+    rules: &'a GameRules,
+    second_column: impl FnMut(&'a str) -> ParseResult<'a, T>,
+) -> Result<Option<(OpponentMove, T)>, ParseError> {
+    let round = terminated(
+        pair(opponent_move(rules), second_column),
+        context("expected the end of the line or a comment", end_of_line),
+    );
+
+    // Once a line is known not to be blank, it has to be a round:
+    // `cut` stops the parser from backtracking and losing the reason the round was invalid.
+    let mut parser = preceded(
+        multispace0,
+        alt((map(end_of_line, |_| None), map(cut(round), Some))),
+    );
+
+    match parser(line) {
+        Ok((_, round)) => Ok(round),
+        Err(nom::Err::Error(err) | nom::Err::Failure(err)) => {
+            Err(convert_error(number, line, rules, err))
+        }
+        Err(nom::Err::Incomplete(_)) => Err(ParseError::line("incomplete round", number, line)),
+    }
+}
+
+/// Convert the error from the parser into one that points at the offending text in the line.
+fn convert_error(
+    number: usize,
+    line: &str,
+    rules: &GameRules,
+    err: VerboseError<&str>,
+) -> ParseError {
+    // The first error is the innermost one, which is where parsing actually failed.
+    // The outer errors then add context about what the parser was looking for.
+    let location = err.errors.first().map(|(input, _)| *input).unwrap_or(line);
+    let context = err
+        .errors
+        .iter()
+        .find_map(|(_, kind)| match kind {
+            VerboseErrorKind::Context(context) => Some(*context),
+            _ => None,
+        })
+        .unwrap_or("invalid round");
+
+    let message = match context {
+        OPPONENT_MOVE => {
+            let symbols = rules
+                .shapes()
+                .map(|shape| rules.rule(shape).opponent_symbol);
+            format!("{context} {}", describe_symbols(symbols))
+        }
+        PLAYER_MOVE => {
+            let symbols = rules.shapes().map(|shape| rules.rule(shape).player_symbol);
+            format!("{context} {}", describe_symbols(symbols))
+        }
+        OUTCOME => {
+            let symbols = rules.outcomes().iter().map(|(_, rule)| rule.symbol);
+            format!("{context} {}", describe_symbols(symbols))
+        }
+        _ => context.to_owned(),
+    };
+
+    let text = location
+        .split(char::is_whitespace)
+        .next()
+        .unwrap_or(location);
+    ParseError::span(message, number, line, text)
+}
+
+/// Describe a list of symbols for an error message, e.g. `'A', 'B' or 'C'`.
+fn describe_symbols(symbols: impl Iterator<Item = char>) -> String {
+    let symbols = symbols
+        .map(|symbol| format!("'{symbol}'"))
+        .collect::<Vec<_>>();
+    match symbols.split_last() {
+        Some((last, [])) => last.to_owned(),
+        Some((last, rest)) => format!("{} or {last}", rest.join(", ")),
+        None => String::from("nothing"),
+    }
+}
+
+/// Any trailing whitespace and comment, followed by the end of the line.
+fn end_of_line(input: &str) -> ParseResult<'_, ()> {
+    map(tuple((multispace0, opt(comment), eof)), |_| ())(input)
+}
+
+/// A comment, which runs from `#` to the end of the line.
+fn comment(input: &str) -> ParseResult<'_, &str> {
+    preceded(char('#'), rest)(input)
+}
+
+fn opponent_move<'a>(
+    rules: &'a GameRules,
+) -> impl FnMut(&'a str) -> ParseResult<'a, OpponentMove> + Copy {
+    move |input| {
+        let symbol = map_opt(anychar, |symbol| rules.by_opponent_symbol(symbol));
+        context(OPPONENT_MOVE, symbol)(input)
+    }
+}
+
+pub(super) fn player_move<'a>(
+    rules: &'a GameRules,
+) -> impl FnMut(&'a str) -> ParseResult<'a, PlayerMove> + Copy {
+    move |input| {
+        let symbol = map_opt(anychar, |symbol| rules.by_player_symbol(symbol));
+        column(context(PLAYER_MOVE, symbol))(input)
+    }
+}
+
+pub(super) fn player_constraint<'a>(
+    rules: &'a GameRules,
+) -> impl FnMut(&'a str) -> ParseResult<'a, PlayerConstraint> + Copy {
+    move |input| {
+        let symbol = map_opt(anychar, |symbol| rules.by_outcome_symbol(symbol));
+        column(context(OUTCOME, map(symbol, PlayerConstraint::from)))(input)
+    }
+}
+
+/// A second column holding a single symbol, whose meaning is left to the caller.
+pub(super) fn any_symbol(input: &str) -> ParseResult<'_, char> {
+    let symbol = satisfy(|c| !c.is_whitespace() && c != '#');
+    column(context("expected a symbol", symbol))(input)
+}
+
+/// A second column that may or may not be present, and whose contents are ignored.
+pub(super) fn unused_column(input: &str) -> ParseResult<'_, ()> {
+    let contents = take_till1(|c: char| c.is_whitespace() || c == '#');
+    map(opt(column(contents)), |_| ())(input)
+}
+
+/// Whitespace separating the first column of a round from the column parsed by `parser`.
+fn column<'a, T>(
+    parser: impl FnMut(&'a str) -> ParseResult<'a, T>,
+) -> impl FnMut(&'a str) -> ParseResult<'a, T> {
+    preceded(
+        context("expected whitespace between the columns", space1),
+        parser,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describe() {
+        assert_eq!(describe_symbols("ABC".chars()), "'A', 'B' or 'C'");
+        assert_eq!(describe_symbols("X".chars()), "'X'");
+    }
+}
@@ -0,0 +1,297 @@
+use std::{cmp::Reverse, collections::HashMap};
+
+use stable_eyre::Report;
+
+use super::{
+    parse::parse_rounds, parse::unused_column, part1_with, part2_with, report::MatchReport,
+    round_score, GameRules, OpponentMove, PlayerMove,
+};
+
+/// Predicts the opponent's next move from the moves they've made so far.
+pub trait Predictor {
+    /// A short description of the predictor, e.g. `markov(2)`.
+    fn name(&self) -> String;
+
+    /// How likely the opponent is to throw each shape next, as relative weights indexed by [`Shape::index`](super::Shape::index).
+    /// The weights are all zero if the predictor has nothing to go on.
+    fn predict(&self, shapes: usize) -> Vec<usize>;
+
+    /// Record the move the opponent actually made.
+    fn observe(&mut self, opponent: OpponentMove);
+}
+
+/// Predicts that the opponent throws each shape as often as they have so far.
+#[derive(Debug, Clone, Default)]
+pub struct FrequencyPredictor {
+    counts: Vec<usize>,
+}
+
+impl FrequencyPredictor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Predictor for FrequencyPredictor {
+    fn name(&self) -> String {
+        String::from("frequency")
+    }
+
+    fn predict(&self, shapes: usize) -> Vec<usize> {
+        let mut weights = self.counts.clone();
+        weights.resize(shapes, 0);
+        weights
+    }
+
+    fn observe(&mut self, opponent: OpponentMove) {
+        increment(&mut self.counts, opponent);
+    }
+}
+
+/// Predicts the opponent's next move from what they threw after their last `order` moves previously.
+#[derive(Debug, Clone)]
+pub struct MarkovPredictor {
+    order: usize,
+    history: Vec<OpponentMove>,
+    transitions: HashMap<Vec<OpponentMove>, Vec<usize>>,
+}
+
+impl MarkovPredictor {
+    pub fn new(order: usize) -> Self {
+        Self {
+            order,
+            history: Vec::with_capacity(order + 1),
+            transitions: HashMap::new(),
+        }
+    }
+}
+
+impl Predictor for MarkovPredictor {
+    fn name(&self) -> String {
+        format!("markov({})", self.order)
+    }
+
+    fn predict(&self, shapes: usize) -> Vec<usize> {
+        let mut weights = match self.transitions.get(&self.history) {
+            Some(counts) if self.history.len() == self.order => counts.clone(),
+            _ => Vec::new(),
+        };
+        weights.resize(shapes, 0);
+        weights
+    }
+
+    fn observe(&mut self, opponent: OpponentMove) {
+        if self.history.len() == self.order {
+            let counts = self.transitions.entry(self.history.clone()).or_default();
+            increment(counts, opponent);
+        }
+
+        // Only the last `order` moves are needed to look up the transitions.
+        self.history.push(opponent);
+        if self.history.len() > self.order {
+            self.history.remove(0);
+        }
+    }
+}
+
+/// Predicts that the opponent repeats themselves: finds the longest run of recent moves (up to `longest`)
+/// that has happened before, and predicts the move that followed it the last time.
+#[derive(Debug, Clone)]
+pub struct PatternPredictor {
+    longest: usize,
+    history: Vec<OpponentMove>,
+
+    /// The move that most recently followed each run of moves seen so far.
+    followers: HashMap<Vec<OpponentMove>, OpponentMove>,
+}
+
+impl PatternPredictor {
+    pub fn new(longest: usize) -> Self {
+        Self {
+            longest,
+            history: Vec::new(),
+            followers: HashMap::new(),
+        }
+    }
+}
+
+impl Predictor for PatternPredictor {
+    fn name(&self) -> String {
+        format!("pattern({})", self.longest)
+    }
+
+    fn predict(&self, shapes: usize) -> Vec<usize> {
+        let mut weights = vec![0; shapes];
+        let longest = self.longest.min(self.history.len());
+        let follower = (1..=longest).rev().find_map(|length| {
+            self.followers
+                .get(&self.history[self.history.len() - length..])
+        });
+        if let Some(follower) = follower {
+            weights[follower.index()] = 1;
+        }
+        weights
+    }
+
+    fn observe(&mut self, opponent: OpponentMove) {
+        let longest = self.longest.min(self.history.len());
+        for length in 1..=longest {
+            let run = self.history[self.history.len() - length..].to_vec();
+            self.followers.insert(run, opponent);
+        }
+
+        // Only the last `longest` moves are needed to look up the runs.
+        self.history.push(opponent);
+        if self.history.len() > self.longest {
+            self.history.remove(0);
+        }
+    }
+}
+
+/// Increment the count for the shape, growing the counts if they don't cover it yet.
+fn increment(counts: &mut Vec<usize>, shape: OpponentMove) {
+    if counts.len() <= shape.index() {
+        counts.resize(shape.index() + 1, 0);
+    }
+    counts[shape.index()] += 1;
+}
+
+/// The move that scores the most on average against the predicted moves.
+/// If the prediction has nothing to go on, every shape is considered equally likely.
+pub(super) fn best_response(rules: &GameRules, weights: &[usize]) -> PlayerMove {
+    let uniform = weights.iter().all(|weight| *weight == 0);
+    let expected_score = |player: PlayerMove| -> usize {
+        rules
+            .shapes()
+            .zip(weights)
+            .map(|(opponent, weight)| {
+                let weight = if uniform { 1 } else { *weight };
+                weight * round_score(rules, opponent, player)
+            })
+            .sum()
+    };
+
+    // Shapes are never empty in valid rules, and on ties the first shape defined wins.
+    rules
+        .shapes()
+        .min_by_key(|player| Reverse(expected_score(*player)))
+        .expect("rules must have shapes")
+}
+
+/// Ignore the second column of the guide, and instead play the best response to the move
+/// the predictor expects the opponent to make each round.
+pub fn play(
+    input: &str,
+    rules: &GameRules,
+    predictor: &mut dyn Predictor,
+) -> Result<MatchReport, Report> {
+    let rounds = parse_rounds(input, rules, unused_column)?
+        .into_iter()
+        .map(|(opponent, _)| {
+            let player = best_response(rules, &predictor.predict(rules.len()));
+            predictor.observe(opponent);
+            (opponent, player)
+        })
+        .collect::<Vec<_>>();
+    Ok(MatchReport::new(rules, &rounds))
+}
+
+/// The predictors compared by [`compare`].
+pub fn predictors() -> Vec<Box<dyn Predictor>> {
+    vec![
+        Box::new(FrequencyPredictor::new()),
+        Box::new(MarkovPredictor::new(1)),
+        Box::new(MarkovPredictor::new(2)),
+        Box::new(MarkovPredictor::new(3)),
+        Box::new(PatternPredictor::new(8)),
+    ]
+}
+
+/// See how exploitable the opponent is: compare the scores from following the guide as in
+/// [`part1`](super::part1) and [`part2`](super::part2) against the scores from each of the [`predictors`].
+pub fn compare(input: &str, rules: &GameRules) -> Result<Vec<(String, usize)>, Report> {
+    let mut scores = vec![
+        (String::from("guide as moves"), part1_with(input, rules)?),
+        (String::from("guide as outcomes"), part2_with(input, rules)?),
+    ];
+    for mut predictor in predictors() {
+        let report = play(input, rules, predictor.as_mut())?;
+        scores.push((predictor.name(), report.score()));
+    }
+    Ok(scores)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day2::{Shape, INPUT, ROCK_PAPER_SCISSORS};
+
+    /// The opponent cycles through Rock, Paper and Scissors.
+    const CYCLE: &str = "A\nB\nC\nA\nB\nC\nA\nB\nC\nA\nB\nC\n";
+
+    #[test]
+    fn frequency() {
+        let mut predictor = FrequencyPredictor::new();
+        assert_eq!(predictor.predict(3), vec![0, 0, 0]);
+        predictor.observe(Shape::PAPER);
+        predictor.observe(Shape::PAPER);
+        predictor.observe(Shape::ROCK);
+        assert_eq!(predictor.predict(3), vec![1, 2, 0]);
+        assert_eq!(
+            best_response(&ROCK_PAPER_SCISSORS, &predictor.predict(3)),
+            Shape::SCISSORS
+        );
+    }
+
+    #[test]
+    fn markov() -> Result<(), Report> {
+        let mut predictor = MarkovPredictor::new(1);
+        predictor.observe(Shape::ROCK);
+        predictor.observe(Shape::PAPER);
+        predictor.observe(Shape::ROCK);
+        assert_eq!(predictor.predict(3), vec![0, 1, 0]);
+
+        // With nothing to go on, Scissors scores the most on average, which only wins the second round.
+        // From the fifth round on, every move in the cycle has been seen before and is anticipated.
+        let report = play(CYCLE, &ROCK_PAPER_SCISSORS, &mut MarkovPredictor::new(1))?;
+        assert_eq!(report.wins, 9);
+        Ok(())
+    }
+
+    #[test]
+    fn pattern() -> Result<(), Report> {
+        let mut predictor = PatternPredictor::new(3);
+        for shape in [
+            Shape::ROCK,
+            Shape::ROCK,
+            Shape::PAPER,
+            Shape::SCISSORS,
+            Shape::ROCK,
+        ] {
+            predictor.observe(shape);
+        }
+
+        // The last time a lone Rock was seen, it was followed by Paper.
+        // The longer run of Scissors, Rock hasn't been seen before.
+        assert_eq!(predictor.predict(3), vec![0, 1, 0]);
+        predictor.observe(Shape::ROCK);
+
+        // Rock, Rock was followed by Paper, even though a lone Rock was most recently followed by another Rock.
+        assert_eq!(predictor.predict(3), vec![0, 1, 0]);
+        assert_eq!(predictor.history.len(), 3);
+
+        // As with the Markov chain, the cycle is anticipated from the fifth round on.
+        let report = play(CYCLE, &ROCK_PAPER_SCISSORS, &mut PatternPredictor::new(3))?;
+        assert_eq!(report.wins, 9);
+        Ok(())
+    }
+
+    #[test]
+    fn compare_input() -> Result<(), Report> {
+        let scores = compare(INPUT, &ROCK_PAPER_SCISSORS)?;
+        assert_eq!(scores.len(), 2 + predictors().len());
+        assert_eq!(scores[0].1, 11386);
+        assert_eq!(scores[1].1, 13600);
+        Ok(())
+    }
+}
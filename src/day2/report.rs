@@ -0,0 +1,389 @@
+use std::{borrow::Cow, fmt::Write, str::FromStr};
+
+use serde_json::{json, Value};
+use stable_eyre::{
+    eyre::{bail, Context},
+    Report,
+};
+
+use super::{
+    evaluate_round, parse::parse_rounds, parse::player_constraint, parse::player_move,
+    reconstruct_rounds, GameRules, OpponentMove, PlayerMove, Round,
+};
+
+/// How a single round played out.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RoundReport {
+    pub opponent: OpponentMove,
+    pub player: PlayerMove,
+    pub outcome: Round,
+
+    /// The score the player earned for the shape they chose.
+    pub shape_score: usize,
+
+    /// The score the player earned for the outcome of the round.
+    pub outcome_score: usize,
+
+    /// The player's total score after this round.
+    pub running_score: usize,
+}
+
+impl RoundReport {
+    /// The score the player earned in this round.
+    pub fn score(&self) -> usize {
+        self.shape_score + self.outcome_score
+    }
+}
+
+/// How every round of a strategy guide played out, along with statistics about the whole match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchReport {
+    /// Each round, in the order they were played.
+    pub rounds: Vec<RoundReport>,
+
+    /// The number of rounds the player won.
+    pub wins: usize,
+
+    /// The number of rounds that were drawn.
+    pub draws: usize,
+
+    /// The number of rounds the player lost.
+    pub losses: usize,
+
+    /// How many times the opponent threw each shape, in the order the shapes are defined in the rules.
+    pub opponent_moves: Vec<(OpponentMove, usize)>,
+
+    /// The most rounds the player won in a row.
+    pub longest_win_streak: usize,
+}
+
+impl MatchReport {
+    /// Report on the rounds played with the given rules.
+    pub fn new(rules: &GameRules, rounds: &[(OpponentMove, PlayerMove)]) -> Self {
+        let mut report = Self {
+            rounds: Vec::with_capacity(rounds.len()),
+            wins: 0,
+            draws: 0,
+            losses: 0,
+            opponent_moves: rules.shapes().map(|shape| (shape, 0)).collect(),
+            longest_win_streak: 0,
+        };
+
+        let mut running_score = 0;
+        let mut win_streak = 0;
+        for (opponent, player) in rounds.iter().copied() {
+            let outcome = evaluate_round(rules, opponent, player);
+            let shape_score = rules.score(player);
+            let outcome_score = rules.outcome_score(outcome);
+            running_score += shape_score + outcome_score;
+
+            match outcome {
+                Round::PlayerWin => report.wins += 1,
+                Round::Draw => report.draws += 1,
+                Round::PlayerLose => report.losses += 1,
+            }
+            report.opponent_moves[opponent.index()].1 += 1;
+
+            win_streak = if outcome == Round::PlayerWin {
+                win_streak + 1
+            } else {
+                0
+            };
+            report.longest_win_streak = report.longest_win_streak.max(win_streak);
+
+            report.rounds.push(RoundReport {
+                opponent,
+                player,
+                outcome,
+                shape_score,
+                outcome_score,
+                running_score,
+            });
+        }
+
+        report
+    }
+
+    /// The player's total score.
+    pub fn score(&self) -> usize {
+        self.rounds
+            .last()
+            .map(|round| round.running_score)
+            .unwrap_or_default()
+    }
+
+    /// Render the report in the given format, using the names of the shapes from the rules.
+    pub fn render(&self, rules: &GameRules, format: ReportFormat) -> Result<String, Report> {
+        match format {
+            ReportFormat::Table => Ok(self.table(rules)),
+            ReportFormat::Csv => Ok(self.csv(rules)),
+            ReportFormat::Json => {
+                serde_json::to_string_pretty(&self.json(rules)).wrap_err("render report as json")
+            }
+        }
+    }
+
+    /// A table of the rounds, followed by the statistics about the match.
+    fn table(&self, rules: &GameRules) -> String {
+        let name = |shape| rules.rule(shape).name.as_str();
+        let width = rules
+            .shapes()
+            .map(|shape| name(shape).len())
+            .max()
+            .unwrap_or_default()
+            .max("opponent".len());
+
+        let mut table = String::new();
+        let mut line = |line: String| {
+            table.push_str(line.trim_end());
+            table.push('\n');
+        };
+
+        line(format!(
+            "{:>5}  {:<width$}  {:<width$}  {:<7}  {:>5}  {:>7}  {:>5}  {:>7}",
+            "round", "opponent", "player", "outcome", "shape", "outcome", "score", "total"
+        ));
+        for (index, round) in self.rounds.iter().enumerate() {
+            line(format!(
+                "{:>5}  {:<width$}  {:<width$}  {:<7}  {:>5}  {:>7}  {:>5}  {:>7}",
+                index + 1,
+                name(round.opponent),
+                name(round.player),
+                round.outcome.to_string(),
+                round.shape_score,
+                round.outcome_score,
+                round.score(),
+                round.running_score,
+            ));
+        }
+
+        line(String::new());
+        line(format!(
+            "wins: {}, draws: {}, losses: {}",
+            self.wins, self.draws, self.losses
+        ));
+        let opponent_moves = self
+            .opponent_moves
+            .iter()
+            .map(|(shape, count)| format!("{} {count}", name(*shape)))
+            .collect::<Vec<_>>();
+        line(format!("opponent moves: {}", opponent_moves.join(", ")));
+        line(format!("longest win streak: {}", self.longest_win_streak));
+        line(format!("total score: {}", self.score()));
+        table
+    }
+
+    /// One line per round, with a header. The statistics about the match are left out,
+    /// since they can be derived from the rounds.
+    fn csv(&self, rules: &GameRules) -> String {
+        let name = |shape| csv_field(&rules.rule(shape).name);
+        let mut csv = String::from(
+            "round,opponent,player,outcome,shape_score,outcome_score,score,running_score\n",
+        );
+        for (index, round) in self.rounds.iter().enumerate() {
+            writeln!(
+                csv,
+                "{},{},{},{},{},{},{},{}",
+                index + 1,
+                name(round.opponent),
+                name(round.player),
+                round.outcome,
+                round.shape_score,
+                round.outcome_score,
+                round.score(),
+                round.running_score,
+            )
+            .expect("write to string");
+        }
+        csv
+    }
+
+    /// The rounds and the statistics about the match.
+    fn json(&self, rules: &GameRules) -> Value {
+        let name = |shape| rules.rule(shape).name.as_str();
+        let rounds = self
+            .rounds
+            .iter()
+            .map(|round| {
+                json!({
+                    "opponent": name(round.opponent),
+                    "player": name(round.player),
+                    "outcome": round.outcome.to_string(),
+                    "shape_score": round.shape_score,
+                    "outcome_score": round.outcome_score,
+                    "score": round.score(),
+                    "running_score": round.running_score,
+                })
+            })
+            .collect::<Vec<_>>();
+        let opponent_moves = self
+            .opponent_moves
+            .iter()
+            .map(|(shape, count)| (name(*shape).to_owned(), json!(count)))
+            .collect::<serde_json::Map<_, _>>();
+
+        json!({
+            "rounds": rounds,
+            "wins": self.wins,
+            "draws": self.draws,
+            "losses": self.losses,
+            "opponent_moves": opponent_moves,
+            "longest_win_streak": self.longest_win_streak,
+            "score": self.score(),
+        })
+    }
+}
+
+/// Quote a CSV field as RFC 4180 calls for, if it contains anything that would otherwise break up the line.
+fn csv_field(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+/// The formats a [`MatchReport`] can be rendered in.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ReportFormat {
+    Table,
+    Csv,
+    Json,
+}
+
+impl FromStr for ReportFormat {
+    type Err = Report;
+
+    /// Formats are given by name: `table`, `csv` or `json`.
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "table" => Ok(ReportFormat::Table),
+            "csv" => Ok(ReportFormat::Csv),
+            "json" => Ok(ReportFormat::Json),
+            _ => bail!("expected 'table', 'csv' or 'json', got '{input}'"),
+        }
+    }
+}
+
+/// Report on the match played by reading the second column of the guide as moves, as in [`part1`](super::part1).
+pub fn report_part1(input: &str, rules: &GameRules) -> Result<MatchReport, Report> {
+    let rounds = parse_rounds(input, rules, player_move(rules))?;
+    Ok(MatchReport::new(rules, &rounds))
+}
+
+/// Report on the match played by reading the second column of the guide as outcomes, as in [`part2`](super::part2).
+pub fn report_part2(input: &str, rules: &GameRules) -> Result<MatchReport, Report> {
+    let rounds = parse_rounds(input, rules, player_constraint(rules))
+        .and_then(|rounds| reconstruct_rounds(rules, rounds))?;
+    Ok(MatchReport::new(rules, &rounds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day2::{part1, part2, Shape, ShapeRule, INPUT, ROCK_PAPER_SCISSORS};
+
+    const EXAMPLE: &str = "A Y\nB X\nC Z\n";
+
+    #[test]
+    fn report_example() -> Result<(), Report> {
+        let rules = &*ROCK_PAPER_SCISSORS;
+        let report = report_part1(EXAMPLE, rules)?;
+        assert_eq!(
+            report.rounds[0],
+            RoundReport {
+                opponent: Shape::ROCK,
+                player: Shape::PAPER,
+                outcome: Round::PlayerWin,
+                shape_score: 2,
+                outcome_score: 6,
+                running_score: 8,
+            }
+        );
+        assert_eq!(
+            report
+                .rounds
+                .iter()
+                .map(|round| round.running_score)
+                .collect::<Vec<_>>(),
+            vec![8, 9, 15]
+        );
+        assert_eq!((report.wins, report.draws, report.losses), (1, 1, 1));
+        assert_eq!(
+            report.opponent_moves,
+            vec![(Shape::ROCK, 1), (Shape::PAPER, 1), (Shape::SCISSORS, 1)]
+        );
+        assert_eq!(report.longest_win_streak, 1);
+        assert_eq!(report.score(), 15);
+
+        // Every round is the same as the one before, except the last one.
+        let report = report_part2("A Z\nA Z\nB Z\nC X\n", rules)?;
+        assert_eq!(report.longest_win_streak, 3);
+        assert_eq!(report.opponent_moves[Shape::ROCK.index()], (Shape::ROCK, 2));
+        Ok(())
+    }
+
+    #[test]
+    fn report_input() -> Result<(), Report> {
+        let rules = &*ROCK_PAPER_SCISSORS;
+        assert_eq!(report_part1(INPUT, rules)?.score(), part1(INPUT)?);
+        assert_eq!(report_part2(INPUT, rules)?.score(), part2(INPUT)?);
+        Ok(())
+    }
+
+    #[test]
+    fn render() -> Result<(), Report> {
+        let rules = &*ROCK_PAPER_SCISSORS;
+        let report = report_part1(EXAMPLE, rules)?;
+
+        let table = report.render(rules, ReportFormat::Table)?;
+        let mut lines = table.lines();
+        assert_eq!(
+            lines.next(),
+            Some("round  opponent  player    outcome  shape  outcome  score    total")
+        );
+        assert_eq!(
+            lines.next(),
+            Some("    1  Rock      Paper     win          2        6      8        8")
+        );
+        assert!(table.ends_with("total score: 15\n"));
+
+        let csv = report.render(rules, ReportFormat::Csv)?;
+        assert_eq!(csv.lines().nth(2), Some("2,Paper,Rock,lose,1,0,1,9"));
+
+        let json = serde_json::from_str::<Value>(&report.render(rules, ReportFormat::Json)?)?;
+        assert_eq!(json["rounds"][2]["outcome"], "draw");
+        assert_eq!(json["opponent_moves"]["Scissors"], 1);
+        assert_eq!(json["score"], 15);
+        Ok(())
+    }
+
+    #[test]
+    fn render_csv_quoting() -> Result<(), Report> {
+        let rules = GameRules::new(
+            vec![
+                ShapeRule::new("Rock, obviously", 1, 'A', 'X'),
+                ShapeRule::new("\"Paper\"", 2, 'B', 'Y'),
+                ShapeRule::new("Scissors", 3, 'C', 'Z'),
+            ],
+            &[
+                ("Rock, obviously", "Scissors"),
+                ("\"Paper\"", "Rock, obviously"),
+                ("Scissors", "\"Paper\""),
+            ],
+        )?;
+        let report = report_part1(EXAMPLE, &rules)?;
+        let csv = report.render(&rules, ReportFormat::Csv)?;
+        let mut lines = csv.lines().skip(1);
+        assert_eq!(
+            lines.next(),
+            Some("1,\"Rock, obviously\",\"\"\"Paper\"\"\",win,2,6,8,8")
+        );
+        assert_eq!(
+            lines.next(),
+            Some("2,\"\"\"Paper\"\"\",\"Rock, obviously\",lose,1,0,1,9")
+        );
+        assert_eq!(lines.next(), Some("3,Scissors,Scissors,draw,3,3,6,15"));
+        Ok(())
+    }
+}
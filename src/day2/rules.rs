@@ -0,0 +1,447 @@
+use std::{collections::HashSet, fmt::Display};
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use stable_eyre::{
+    eyre::{bail, ensure},
+    Report,
+};
+
+use super::Round;
+
+/// The classic game: Rock beats Scissors, Scissors beats Paper, and Paper beats Rock.
+///
+/// The opponent writes these as A, B and C, and the player as X, Y and Z.
+pub static ROCK_PAPER_SCISSORS: Lazy<GameRules> = Lazy::new(|| {
+    let shapes = vec![
+        ShapeRule::new("Rock", 1, 'A', 'X'),
+        ShapeRule::new("Paper", 2, 'B', 'Y'),
+        ShapeRule::new("Scissors", 3, 'C', 'Z'),
+    ];
+    let beats = [
+        ("Rock", "Scissors"),
+        ("Scissors", "Paper"),
+        ("Paper", "Rock"),
+    ];
+
+    // The rules are fixed, and `builtin_rules_valid` ensures they're correct.
+    GameRules::new(shapes, &beats).expect("rock paper scissors must be valid")
+});
+
+/// The classic game, extended with Lizard and Spock so that each shape beats two others.
+///
+/// The opponent writes these as A through E, and the player as X, Y, Z, V and W,
+/// so that the classic shapes keep their usual symbols.
+pub static ROCK_PAPER_SCISSORS_LIZARD_SPOCK: Lazy<GameRules> = Lazy::new(|| {
+    let shapes = vec![
+        ShapeRule::new("Rock", 1, 'A', 'X'),
+        ShapeRule::new("Paper", 2, 'B', 'Y'),
+        ShapeRule::new("Scissors", 3, 'C', 'Z'),
+        ShapeRule::new("Lizard", 4, 'D', 'V'),
+        ShapeRule::new("Spock", 5, 'E', 'W'),
+    ];
+    let beats = [
+        ("Scissors", "Paper"),
+        ("Paper", "Rock"),
+        ("Rock", "Lizard"),
+        ("Lizard", "Spock"),
+        ("Spock", "Scissors"),
+        ("Scissors", "Lizard"),
+        ("Lizard", "Paper"),
+        ("Paper", "Spock"),
+        ("Spock", "Rock"),
+        ("Rock", "Scissors"),
+    ];
+
+    // The rules are fixed, and `builtin_rules_valid` ensures they're correct.
+    GameRules::new(shapes, &beats).expect("rock paper scissors lizard spock must be valid")
+});
+
+/// A shape that can be thrown, identified by its position in the [`GameRules`] it belongs to.
+///
+/// Shapes should only be used with the rules they came from.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Shape(usize);
+
+impl Shape {
+    /// Rock, in both of the built-in rule sets.
+    pub const ROCK: Shape = Shape(0);
+
+    /// Paper, in both of the built-in rule sets.
+    pub const PAPER: Shape = Shape(1);
+
+    /// Scissors, in both of the built-in rule sets.
+    pub const SCISSORS: Shape = Shape(2);
+
+    /// Lizard, in [`ROCK_PAPER_SCISSORS_LIZARD_SPOCK`].
+    pub const LIZARD: Shape = Shape(3);
+
+    /// Spock, in [`ROCK_PAPER_SCISSORS_LIZARD_SPOCK`].
+    pub const SPOCK: Shape = Shape(4);
+
+    /// The position of the shape in its rules.
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// What a shape is called, how it's written in a strategy guide, and what it's worth.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShapeRule {
+    /// The name of the shape.
+    pub name: String,
+
+    /// The score the player earns for choosing the shape.
+    pub score: usize,
+
+    /// The symbol used for the shape in the opponent's column of a strategy guide.
+    pub opponent_symbol: char,
+
+    /// The symbol used for the shape in the player's column of a strategy guide.
+    pub player_symbol: char,
+}
+
+impl ShapeRule {
+    pub fn new(
+        name: impl Into<String>,
+        score: usize,
+        opponent_symbol: char,
+        player_symbol: char,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            score,
+            opponent_symbol,
+            player_symbol,
+        }
+    }
+}
+
+/// How an outcome is written in the second column of a strategy guide, and what it's worth.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutcomeRule {
+    /// The symbol used for the outcome in the second column of a strategy guide.
+    pub symbol: char,
+
+    /// The score the player earns when a round ends this way.
+    pub score: usize,
+}
+
+impl OutcomeRule {
+    pub fn new(symbol: char, score: usize) -> Self {
+        Self { symbol, score }
+    }
+}
+
+/// The rule for each way a round can end, from the player's point of view.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutcomeRules {
+    pub lose: OutcomeRule,
+    pub draw: OutcomeRule,
+    pub win: OutcomeRule,
+}
+
+impl OutcomeRules {
+    /// The rule for the outcome.
+    pub fn get(&self, outcome: Round) -> &OutcomeRule {
+        match outcome {
+            Round::PlayerLose => &self.lose,
+            Round::Draw => &self.draw,
+            Round::PlayerWin => &self.win,
+        }
+    }
+
+    /// Every outcome along with its rule, from losing to winning.
+    pub fn iter(&self) -> impl Iterator<Item = (Round, &OutcomeRule)> {
+        [Round::PlayerLose, Round::Draw, Round::PlayerWin]
+            .into_iter()
+            .map(|outcome| (outcome, self.get(outcome)))
+    }
+}
+
+impl Default for OutcomeRules {
+    /// X means lose for 0 points, Y means draw for 3 points, and Z means win for 6 points.
+    fn default() -> Self {
+        Self {
+            lose: OutcomeRule::new('X', 0),
+            draw: OutcomeRule::new('Y', 3),
+            win: OutcomeRule::new('Z', 6),
+        }
+    }
+}
+
+/// The shapes in a game, which shape beats which, and what each shape and outcome is worth.
+#[derive(Debug, Clone)]
+pub struct GameRules {
+    shapes: Vec<ShapeRule>,
+
+    /// The outcome of every pair of shapes, from the player's point of view.
+    /// The outcome of the opponent throwing `o` and the player throwing `p` is at `o * shapes.len() + p`.
+    results: Vec<Round>,
+
+    outcomes: OutcomeRules,
+}
+
+impl GameRules {
+    /// Construct rules from the shapes in the game, and which shape beats which as `(winner, loser)` pairs of names.
+    ///
+    /// Each pair of different shapes must have exactly one winner.
+    /// Outcomes use the [default rules](OutcomeRules::default) until replaced with [`GameRules::with_outcomes`].
+    pub fn new(shapes: Vec<ShapeRule>, beats: &[(&str, &str)]) -> Result<Self, Report> {
+        ensure!(!shapes.is_empty(), "a game must have at least one shape");
+        ensure_unique(shapes.iter().map(|s| s.name.as_str()), "shape name")?;
+        ensure_unique(shapes.iter().map(|s| s.opponent_symbol), "opponent symbol")?;
+        ensure_unique(shapes.iter().map(|s| s.player_symbol), "player symbol")?;
+
+        let count = shapes.len();
+        let position = |name: &str| -> Result<usize, Report> {
+            match shapes.iter().position(|shape| shape.name == name) {
+                Some(position) => Ok(position),
+                None => bail!("unknown shape '{name}'"),
+            }
+        };
+
+        // Every shape draws against itself, everything else has to be filled in by `beats`.
+        let mut results = vec![None; count * count];
+        for shape in 0..count {
+            results[shape * count + shape] = Some(Round::Draw);
+        }
+
+        for (winner, loser) in beats {
+            let (winner_position, loser_position) = (position(winner)?, position(loser)?);
+            ensure!(winner != loser, "'{winner}' can't beat itself");
+
+            let opponent_wins = winner_position * count + loser_position;
+            let player_wins = loser_position * count + winner_position;
+            ensure!(
+                results[opponent_wins].is_none(),
+                "the winner between '{winner}' and '{loser}' is given more than once"
+            );
+
+            results[opponent_wins] = Some(Round::PlayerLose);
+            results[player_wins] = Some(Round::PlayerWin);
+        }
+
+        let results = results
+            .into_iter()
+            .enumerate()
+            .map(|(position, outcome)| match outcome {
+                Some(outcome) => Ok(outcome),
+                None => {
+                    let (first, second) = (&shapes[position / count], &shapes[position % count]);
+                    bail!(
+                        "neither '{}' nor '{}' beats the other",
+                        first.name,
+                        second.name
+                    )
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            shapes,
+            results,
+            outcomes: OutcomeRules::default(),
+        })
+    }
+
+    /// Replace the symbols and scores used for the outcome of a round.
+    ///
+    /// Each outcome must have a different symbol.
+    pub fn with_outcomes(mut self, outcomes: OutcomeRules) -> Result<Self, Report> {
+        ensure_unique(
+            outcomes.iter().map(|(_, rule)| rule.symbol),
+            "outcome symbol",
+        )?;
+        self.outcomes = outcomes;
+        Ok(self)
+    }
+
+    /// Every shape in the game, in the order they were defined.
+    pub fn shapes(&self) -> impl Iterator<Item = Shape> {
+        (0..self.shapes.len()).map(Shape)
+    }
+
+    /// The number of shapes in the game.
+    pub fn len(&self) -> usize {
+        self.shapes.len()
+    }
+
+    /// Whether the game has no shapes.
+    /// Rules constructed by [`GameRules::new`] always have at least one.
+    pub fn is_empty(&self) -> bool {
+        self.shapes.is_empty()
+    }
+
+    /// The definition of the shape.
+    pub fn rule(&self, shape: Shape) -> &ShapeRule {
+        &self.shapes[shape.0]
+    }
+
+    /// The score the player earns for choosing the shape.
+    pub fn score(&self, shape: Shape) -> usize {
+        self.rule(shape).score
+    }
+
+    /// Look up a shape by its name.
+    pub fn by_name(&self, name: &str) -> Option<Shape> {
+        self.shapes
+            .iter()
+            .position(|shape| shape.name == name)
+            .map(Shape)
+    }
+
+    /// Look up a shape by the symbol used for it in the opponent's column.
+    pub fn by_opponent_symbol(&self, symbol: char) -> Option<Shape> {
+        self.shapes
+            .iter()
+            .position(|shape| shape.opponent_symbol == symbol)
+            .map(Shape)
+    }
+
+    /// Look up a shape by the symbol used for it in the player's column.
+    pub fn by_player_symbol(&self, symbol: char) -> Option<Shape> {
+        self.shapes
+            .iter()
+            .position(|shape| shape.player_symbol == symbol)
+            .map(Shape)
+    }
+
+    /// The rules for each outcome of a round.
+    pub fn outcomes(&self) -> &OutcomeRules {
+        &self.outcomes
+    }
+
+    /// The score the player earns when a round ends with the outcome.
+    pub fn outcome_score(&self, outcome: Round) -> usize {
+        self.outcomes.get(outcome).score
+    }
+
+    /// Look up an outcome by the symbol used for it in the second column.
+    pub fn by_outcome_symbol(&self, symbol: char) -> Option<Round> {
+        self.outcomes
+            .iter()
+            .find(|(_, rule)| rule.symbol == symbol)
+            .map(|(outcome, _)| outcome)
+    }
+
+    /// Which shape beats which, as `(winner, loser)` pairs.
+    pub fn beats(&self) -> impl Iterator<Item = (Shape, Shape)> + '_ {
+        self.shapes().flat_map(move |winner| {
+            self.shapes()
+                .filter(move |loser| self.evaluate(*loser, winner) == Round::PlayerWin)
+                .map(move |loser| (winner, loser))
+        })
+    }
+
+    /// The outcome of a round, from the player's point of view.
+    pub fn evaluate(&self, opponent: Shape, player: Shape) -> Round {
+        self.results[opponent.0 * self.shapes.len() + player.0]
+    }
+}
+
+/// Ensure that no value is repeated, describing the values as `kind` in the error.
+fn ensure_unique<T: Display>(values: impl Iterator<Item = T>, kind: &str) -> Result<(), Report> {
+    let mut seen = HashSet::new();
+    for value in values {
+        ensure!(
+            seen.insert(value.to_string()),
+            "{kind} '{value}' is used more than once"
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_rules_valid() {
+        assert_eq!(ROCK_PAPER_SCISSORS.len(), 3);
+        assert_eq!(ROCK_PAPER_SCISSORS_LIZARD_SPOCK.len(), 5);
+    }
+
+    #[test]
+    fn rock_paper_scissors() {
+        let rules = &*ROCK_PAPER_SCISSORS;
+        assert_eq!(rules.evaluate(Shape::ROCK, Shape::PAPER), Round::PlayerWin);
+        assert_eq!(rules.evaluate(Shape::PAPER, Shape::ROCK), Round::PlayerLose);
+        assert_eq!(
+            rules.evaluate(Shape::SCISSORS, Shape::SCISSORS),
+            Round::Draw
+        );
+        assert_eq!(rules.by_opponent_symbol('C'), Some(Shape::SCISSORS));
+        assert_eq!(rules.by_player_symbol('Y'), Some(Shape::PAPER));
+        assert_eq!(rules.by_name("Rock"), Some(Shape::ROCK));
+        assert_eq!(rules.score(Shape::SCISSORS), 3);
+    }
+
+    #[test]
+    fn rock_paper_scissors_lizard_spock() {
+        let rules = &*ROCK_PAPER_SCISSORS_LIZARD_SPOCK;
+
+        // Every shape beats exactly two others, and loses to the remaining two.
+        for opponent in rules.shapes() {
+            let wins = rules
+                .shapes()
+                .filter(|player| rules.evaluate(opponent, *player) == Round::PlayerWin)
+                .count();
+            assert_eq!(wins, 2);
+        }
+
+        assert_eq!(
+            rules.evaluate(Shape::SPOCK, Shape::LIZARD),
+            Round::PlayerWin
+        );
+        assert_eq!(rules.evaluate(Shape::ROCK, Shape::SPOCK), Round::PlayerWin);
+        assert_eq!(rules.evaluate(Shape::LIZARD, Shape::ROCK), Round::PlayerWin);
+        assert_eq!(rules.by_player_symbol('W'), Some(Shape::SPOCK));
+    }
+
+    #[test]
+    fn invalid_rules() {
+        let shapes = || {
+            vec![
+                ShapeRule::new("Rock", 1, 'A', 'X'),
+                ShapeRule::new("Paper", 2, 'B', 'Y'),
+            ]
+        };
+        assert!(GameRules::new(shapes(), &[("Paper", "Rock")]).is_ok());
+        assert!(GameRules::new(shapes(), &[]).is_err());
+        assert!(GameRules::new(shapes(), &[("Paper", "Rock"), ("Rock", "Paper")]).is_err());
+        assert!(GameRules::new(shapes(), &[("Paper", "Paper")]).is_err());
+        assert!(GameRules::new(shapes(), &[("Paper", "Stone")]).is_err());
+        assert!(GameRules::new(vec![], &[]).is_err());
+
+        let duplicate = vec![
+            ShapeRule::new("Rock", 1, 'A', 'X'),
+            ShapeRule::new("Paper", 2, 'A', 'Y'),
+        ];
+        assert!(GameRules::new(duplicate, &[("Paper", "Rock")]).is_err());
+
+        let outcomes = OutcomeRules {
+            draw: OutcomeRule::new('X', 3),
+            ..OutcomeRules::default()
+        };
+        let rules = GameRules::new(shapes(), &[("Paper", "Rock")]).expect("must be valid");
+        assert!(rules.with_outcomes(outcomes).is_err());
+    }
+
+    #[test]
+    fn outcomes() -> Result<(), Report> {
+        let rules = ROCK_PAPER_SCISSORS.clone();
+        assert_eq!(rules.by_outcome_symbol('Z'), Some(Round::PlayerWin));
+        assert_eq!(rules.outcome_score(Round::Draw), 3);
+
+        let rules = rules.with_outcomes(OutcomeRules {
+            lose: OutcomeRule::new('L', 1),
+            draw: OutcomeRule::new('D', 2),
+            win: OutcomeRule::new('W', 4),
+        })?;
+        assert_eq!(rules.by_outcome_symbol('Z'), None);
+        assert_eq!(rules.by_outcome_symbol('L'), Some(Round::PlayerLose));
+        assert_eq!(rules.outcome_score(Round::PlayerWin), 4);
+        Ok(())
+    }
+}
@@ -0,0 +1,329 @@
+use std::cmp::{Ordering, Reverse};
+
+use stable_eyre::{
+    eyre::{bail, ensure},
+    Report,
+};
+
+use super::{
+    evaluate_round, parse::parse_rounds, parse::unused_column, round_score, GameRules,
+    OpponentMove, PlayerMove, Round,
+};
+
+/// What [`solve`] should aim for when planning the player's moves.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// Score as many points as possible.
+    Maximise,
+
+    /// Score as few points as possible.
+    Minimise,
+
+    /// Score exactly this many points.
+    Exactly(usize),
+}
+
+/// The moves the player should make over the course of a tournament.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plan {
+    /// The move to make in each round.
+    pub moves: Vec<PlayerMove>,
+
+    /// The total score the moves earn.
+    pub score: usize,
+
+    /// The number of rounds the moves win.
+    pub wins: usize,
+}
+
+/// Winning every time would be suspicious, so the responses must be carefully chosen.
+///
+/// Given only the opponent's moves (the second column of the guide is ignored if present),
+/// plan the player's moves so that their total score hits the `target`,
+/// without winning more than `max_wins` rounds if a limit is provided.
+pub fn solve(
+    input: &str,
+    rules: &GameRules,
+    target: Target,
+    max_wins: Option<usize>,
+) -> Result<Plan, Report> {
+    let options = parse_rounds(input, rules, unused_column)?
+        .into_iter()
+        .map(|(opponent, _)| round_options(rules, opponent))
+        .collect::<Vec<_>>();
+    let max_wins = max_wins.unwrap_or(options.len());
+
+    let choices = match target {
+        Target::Maximise => plan_extreme(&options, max_wins, Ordering::Greater)?,
+        Target::Minimise => plan_extreme(&options, max_wins, Ordering::Less)?,
+        Target::Exactly(score) => plan_exact(&options, max_wins, score)?,
+    };
+
+    Ok(Plan {
+        score: total_score(choices.iter().map(|choice| choice.score))?,
+        wins: choices.iter().filter(|choice| choice.wins()).count(),
+        moves: choices.into_iter().map(|choice| choice.player).collect(),
+    })
+}
+
+/// One way the player could play a single round.
+#[derive(Copy, Clone)]
+struct RoundOption {
+    player: PlayerMove,
+    outcome: Round,
+    score: usize,
+}
+
+impl RoundOption {
+    fn wins(&self) -> bool {
+        self.outcome == Round::PlayerWin
+    }
+}
+
+/// Every way the player could play against the opponent's move.
+fn round_options(rules: &GameRules, opponent: OpponentMove) -> Vec<RoundOption> {
+    rules
+        .shapes()
+        .map(|player| RoundOption {
+            player,
+            outcome: evaluate_round(rules, opponent, player),
+            score: round_score(rules, opponent, player),
+        })
+        .collect()
+}
+
+/// Plan the rounds to score the most points (or the fewest points, if `prefer` is `Ordering::Less`)
+/// while winning at most `max_wins` rounds.
+fn plan_extreme(
+    options: &[Vec<RoundOption>],
+    max_wins: usize,
+    prefer: Ordering,
+) -> Result<Vec<RoundOption>, Report> {
+    let better = |a: &RoundOption, b: &RoundOption| a.score.cmp(&b.score) == prefer;
+    let best = |options: &[RoundOption], wins: bool| {
+        options
+            .iter()
+            .filter(|option| option.wins() == wins)
+            .copied()
+            .reduce(|best, option| if better(&option, &best) { option } else { best })
+    };
+
+    // Start with the best option for each round that doesn't win it,
+    // noting which rounds would be improved by winning them instead.
+    let mut plan = Vec::with_capacity(options.len());
+    let mut upgrades = Vec::new();
+    for (round, options) in options.iter().enumerate() {
+        let Some(other) = best(options, false) else { bail!("round {} can only be won", round + 1) };
+        if let Some(win) = best(options, true).filter(|win| better(win, &other)) {
+            upgrades.push((round, win, win.score.abs_diff(other.score)));
+        }
+        plan.push(other);
+    }
+
+    // Then switch the rounds that benefit the most from winning over to winning,
+    // until we run out of wins that wouldn't be suspicious.
+    upgrades.sort_by_key(|(_, _, benefit)| Reverse(*benefit));
+    for (round, win, _) in upgrades.into_iter().take(max_wins) {
+        plan[round] = win;
+    }
+
+    Ok(plan)
+}
+
+/// The sum of the scores, which can be too large to represent with the scores in some ciphers.
+fn total_score(scores: impl IntoIterator<Item = usize>) -> Result<usize, Report> {
+    let total = scores
+        .into_iter()
+        .try_fold(0usize, |total, score| total.checked_add(score));
+    match total {
+        Some(total) => Ok(total),
+        None => bail!("the total score is too large to represent"),
+    }
+}
+
+/// Plan the rounds to score exactly `target` points while winning at most `max_wins` rounds.
+///
+/// This works through the rounds in order, tracking the fewest wins needed to reach each possible score.
+/// It uses memory proportional to the number of rounds multiplied by the target,
+/// so targets higher than any combination of moves could score are rejected up front.
+fn plan_exact(
+    options: &[Vec<RoundOption>],
+    max_wins: usize,
+    target: usize,
+) -> Result<Vec<RoundOption>, Report> {
+    let highest = total_score(
+        options
+            .iter()
+            .filter_map(|round| round.iter().map(|option| option.score).max()),
+    )?;
+    ensure!(
+        target <= highest,
+        "no combination of moves scores exactly {target}, the most they can score is {highest}"
+    );
+
+    // Scores never decrease from one round to the next, so anything over the target can be discarded.
+    let mut fewest_wins = vec![None; target + 1];
+    fewest_wins[0] = Some(0);
+
+    // For each round and each score, which option was picked to reach that score with the fewest wins.
+    // This is by far the largest allocation, so picks are stored as bytes.
+    const NO_PICK: u8 = u8::MAX;
+    let mut picks = Vec::with_capacity(options.len());
+    for round in options {
+        ensure!(
+            round.len() < usize::from(NO_PICK),
+            "exact planning supports at most {} shapes",
+            NO_PICK - 1
+        );
+
+        let mut next_fewest_wins: Vec<Option<usize>> = vec![None; target + 1];
+        let mut next_picks = vec![NO_PICK; target + 1];
+
+        for (score, wins) in fewest_wins.iter().enumerate() {
+            let Some(wins) = wins else { continue };
+            for (pick, option) in (0..).zip(round) {
+                let Some(score) = score
+                    .checked_add(option.score)
+                    .filter(|score| *score <= target)
+                else {
+                    continue;
+                };
+
+                let wins = wins + usize::from(option.wins());
+                if next_fewest_wins[score].is_none_or(|fewest| wins < fewest) {
+                    next_fewest_wins[score] = Some(wins);
+                    next_picks[score] = pick;
+                }
+            }
+        }
+
+        fewest_wins = next_fewest_wins;
+        picks.push(next_picks);
+    }
+
+    match fewest_wins[target] {
+        Some(wins) if wins <= max_wins => {}
+        Some(wins) => bail!("scoring exactly {target} requires at least {wins} wins, more than the limit of {max_wins}"),
+        None => bail!("no combination of moves scores exactly {target}"),
+    }
+
+    // Walk back through the rounds, following the picks that led to the target.
+    let mut plan = Vec::with_capacity(options.len());
+    let mut score = target;
+    for (round, picks) in options.iter().zip(picks).rev() {
+        let Some(&option) = round.get(usize::from(picks[score])) else { bail!("no pick recorded for score {score}") };
+        plan.push(option);
+        score -= option.score;
+    }
+
+    plan.reverse();
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day2::{
+        part1, Shape, ShapeRule, INPUT, ROCK_PAPER_SCISSORS, ROCK_PAPER_SCISSORS_LIZARD_SPOCK,
+    };
+
+    const EXAMPLE: &str = "A Y\nB X\nC Z\n";
+
+    #[test]
+    fn solve_maximise() -> Result<(), Report> {
+        let plan = solve(EXAMPLE, &ROCK_PAPER_SCISSORS, Target::Maximise, None)?;
+        assert_eq!(plan.score, 24);
+        assert_eq!(plan.wins, 3);
+        assert_eq!(plan.moves, vec![Shape::PAPER, Shape::SCISSORS, Shape::ROCK]);
+
+        // Drawing every round scores 15, and winning the first round is worth the most extra points.
+        let plan = solve(EXAMPLE, &ROCK_PAPER_SCISSORS, Target::Maximise, Some(1))?;
+        assert_eq!(plan.score, 19);
+        assert_eq!(plan.wins, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn solve_minimise() -> Result<(), Report> {
+        let plan = solve("A\nB\nC\n", &ROCK_PAPER_SCISSORS, Target::Minimise, None)?;
+        assert_eq!(plan.score, 6);
+        assert_eq!(plan.wins, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn solve_exactly() -> Result<(), Report> {
+        let rules = &*ROCK_PAPER_SCISSORS;
+        let plan = solve(EXAMPLE, rules, Target::Exactly(15), Some(0))?;
+        assert_eq!(plan.score, 15);
+        assert_eq!(plan.wins, 0);
+
+        let plan = solve(EXAMPLE, rules, Target::Exactly(19), None)?;
+        assert_eq!(plan.score, 19);
+        assert_eq!(plan.wins, 1);
+
+        assert!(solve(EXAMPLE, rules, Target::Exactly(24), Some(2)).is_err());
+        assert!(solve(EXAMPLE, rules, Target::Exactly(100), None).is_err());
+
+        // Targets out of reach are rejected before anything is allocated for them.
+        for target in [usize::MAX, 1 << 40] {
+            let err = solve(EXAMPLE, rules, Target::Exactly(target), None).expect_err("must error");
+            assert!(format!("{err:?}").contains("the most they can score is 24"));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn solve_huge_scores() -> Result<(), Report> {
+        // Any one round fits, but three of them add up to more than can be represented.
+        let rules = GameRules::new(
+            vec![
+                ShapeRule::new("Rock", usize::MAX / 2, 'A', 'X'),
+                ShapeRule::new("Paper", 2, 'B', 'Y'),
+                ShapeRule::new("Scissors", 3, 'C', 'Z'),
+            ],
+            &[
+                ("Rock", "Scissors"),
+                ("Paper", "Rock"),
+                ("Scissors", "Paper"),
+            ],
+        )?;
+        for target in [Target::Exactly(5), Target::Maximise] {
+            let err = solve("A\nA\nA\n", &rules, target, None).expect_err("must error");
+            assert!(format!("{err:?}").contains("the total score is too large to represent"));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn solve_lizard_spock() -> Result<(), Report> {
+        // Spock beats Rock for 5 + 6, and Spock draws with Spock for 5 + 3.
+        let rules = &*ROCK_PAPER_SCISSORS_LIZARD_SPOCK;
+        let plan = solve("A\nE\n", rules, Target::Maximise, Some(1))?;
+        assert_eq!(plan.moves, vec![Shape::SPOCK, Shape::SPOCK]);
+        assert_eq!(plan.score, 19);
+        Ok(())
+    }
+
+    #[test]
+    fn solve_input() -> Result<(), Report> {
+        let rules = &*ROCK_PAPER_SCISSORS;
+        let opponents = parse_rounds(INPUT, rules, unused_column)?;
+        let limit = opponents.len() / 3;
+        for target in [
+            Target::Maximise,
+            Target::Minimise,
+            Target::Exactly(part1(INPUT)?),
+        ] {
+            let plan = solve(INPUT, rules, target, Some(limit))?;
+            let score = opponents
+                .iter()
+                .zip(&plan.moves)
+                .map(|((opponent, _), player)| round_score(rules, *opponent, *player))
+                .sum::<usize>();
+            assert_eq!(plan.score, score);
+            assert!(plan.wins <= limit);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,338 @@
+use std::{
+    io::BufRead,
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, SyncSender},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use stable_eyre::{
+    eyre::{bail, Context},
+    Report,
+};
+
+use super::{
+    desired_move, fast::ScoreTable, parse::parse_line, parse::player_constraint,
+    parse::player_move, round_score, GameRules,
+};
+
+/// The number of lines handed to a thread at a time when scoring in parallel.
+/// At most one chunk per thread is waiting to be scored at any time, which bounds the memory used.
+const CHUNK_LINES: usize = 16 * 1024;
+
+/// Solve [`part1`](super::part1) for a game played with the given rules, reading the guide one line at a time.
+///
+/// Each round is scored as soon as it's read, so only one line of the guide is held in memory at any time.
+pub fn part1_reader(reader: impl BufRead, rules: &GameRules) -> Result<usize, Report> {
+    let table = ScoreTable::new(rules)?;
+    score_reader(reader, |number, line| {
+        table.score_move_line(rules, number, line)
+    })
+}
+
+/// Solve [`part2`](super::part2) for a game played with the given rules, reading the guide one line at a time.
+///
+/// Each round is scored as soon as it's read, so only one line of the guide is held in memory at any time.
+pub fn part2_reader(reader: impl BufRead, rules: &GameRules) -> Result<usize, Report> {
+    let table = ScoreTable::new(rules)?;
+    score_reader(reader, |number, line| {
+        table.score_outcome_line(rules, number, line)
+    })
+}
+
+/// Solve [`part1`](super::part1) like [`part1_reader`], scoring chunks of the guide across `threads` threads.
+///
+/// If more than one line of the guide is invalid, the error for the earliest one is reported.
+pub fn part1_parallel(
+    reader: impl BufRead,
+    rules: &GameRules,
+    threads: NonZeroUsize,
+) -> Result<usize, Report> {
+    let table = ScoreTable::new(rules)?;
+    score_parallel(reader, threads, CHUNK_LINES, |number, line| {
+        table.score_move_line(rules, number, line)
+    })
+}
+
+/// Solve [`part2`](super::part2) like [`part2_reader`], scoring chunks of the guide across `threads` threads.
+///
+/// If more than one line of the guide is invalid, the error for the earliest one is reported.
+pub fn part2_parallel(
+    reader: impl BufRead,
+    rules: &GameRules,
+    threads: NonZeroUsize,
+) -> Result<usize, Report> {
+    let table = ScoreTable::new(rules)?;
+    score_parallel(reader, threads, CHUNK_LINES, |number, line| {
+        table.score_outcome_line(rules, number, line)
+    })
+}
+
+/// Score line `number` of the guide, reading the second column as the player's move.
+/// Lines without a round score nothing.
+pub(super) fn score_move(rules: &GameRules, number: usize, line: &str) -> Result<usize, Report> {
+    let round = parse_line(number, line, rules, player_move(rules))?;
+    Ok(round.map_or(0, |(opponent, player)| round_score(rules, opponent, player)))
+}
+
+/// Score line `number` of the guide, reading the second column as how the round needs to end.
+/// Lines without a round score nothing.
+pub(super) fn score_outcome(rules: &GameRules, number: usize, line: &str) -> Result<usize, Report> {
+    let Some((opponent, constraint)) = parse_line(number, line, rules, player_constraint(rules))?
+    else {
+        return Ok(0);
+    };
+    let player = desired_move(rules, opponent, constraint)
+        .wrap_err_with(|| format!("reconstruct round on line {number}"))?;
+    Ok(round_score(rules, opponent, player))
+}
+
+/// Score every line of the guide, one at a time.
+fn score_reader<R, F>(mut reader: R, score_line: F) -> Result<usize, Report>
+where
+    R: BufRead,
+    F: Fn(usize, &str) -> Result<usize, Report>,
+{
+    let mut line = String::new();
+    let mut total = 0;
+    for number in 1.. {
+        line.clear();
+        let read = reader
+            .read_line(&mut line)
+            .wrap_err_with(|| format!("read line {number}"))?;
+        if read == 0 {
+            break;
+        }
+
+        let line = line.strip_suffix('\n').unwrap_or(&line);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        total += score_line(number, line)?;
+    }
+    Ok(total)
+}
+
+/// A run of consecutive lines from the guide.
+struct Chunk {
+    /// The line number of the first line in the chunk.
+    first_line: usize,
+    text: String,
+}
+
+/// Score every line of the guide, reading chunks of `chunk_lines` lines on this thread
+/// and scoring them across `threads` other threads.
+fn score_parallel<R, F>(
+    mut reader: R,
+    threads: NonZeroUsize,
+    chunk_lines: usize,
+    score_line: F,
+) -> Result<usize, Report>
+where
+    R: BufRead,
+    F: Fn(usize, &str) -> Result<usize, Report> + Sync,
+{
+    let (sender, receiver) = mpsc::sync_channel(threads.get());
+    let failed = AtomicBool::new(false);
+
+    thread::scope(|scope| {
+        // Only the workers hold on to the receiver, so if they all stop (even by panicking)
+        // it's dropped and the reader stops sending instead of waiting for room in the channel.
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers = (0..threads.get())
+            .map(|_| {
+                let receiver = Arc::clone(&receiver);
+                let (failed, score_line) = (&failed, &score_line);
+                scope.spawn(move || score_chunks(&receiver, failed, score_line))
+            })
+            .collect::<Vec<_>>();
+        drop(receiver);
+
+        // Dropping the sender once everything is read tells the workers there's nothing left to score.
+        let read = read_chunks(&mut reader, chunk_lines, sender, &failed);
+
+        // Every worker is joined before reporting a panic, since the scope panics itself
+        // if any thread it's left to join has panicked.
+        let mut total = 0;
+        let mut panicked = false;
+        let mut earliest_error: Option<(usize, Report)> = None;
+        for worker in workers {
+            match worker.join() {
+                Ok(Ok(score)) => total += score,
+                Ok(Err((line, err))) => {
+                    if earliest_error
+                        .as_ref()
+                        .is_none_or(|(earliest, _)| line < *earliest)
+                    {
+                        earliest_error = Some((line, err));
+                    }
+                }
+                Err(_) => panicked = true,
+            }
+        }
+        if panicked {
+            bail!("a thread scoring the guide panicked");
+        }
+
+        // Chunks are only sent once they've been read in full, so an error scoring them
+        // comes from earlier in the guide than an error reading it.
+        if let Some((_, err)) = earliest_error {
+            return Err(err);
+        }
+        read?;
+        Ok(total)
+    })
+}
+
+/// Read the guide into chunks of `chunk_lines` lines, sending each one to be scored.
+/// Stops early if scoring has `failed`.
+fn read_chunks<R: BufRead>(
+    reader: &mut R,
+    chunk_lines: usize,
+    sender: SyncSender<Chunk>,
+    failed: &AtomicBool,
+) -> Result<(), Report> {
+    let mut number = 1;
+    while !failed.load(Ordering::Relaxed) {
+        let mut chunk = Chunk {
+            first_line: number,
+            text: String::new(),
+        };
+        for _ in 0..chunk_lines {
+            let read = reader
+                .read_line(&mut chunk.text)
+                .wrap_err_with(|| format!("read line {number}"))?;
+            if read == 0 {
+                break;
+            }
+            number += 1;
+        }
+
+        if chunk.text.is_empty() {
+            break;
+        }
+
+        // Workers receive until the sender is dropped, so sending only fails once they've all stopped.
+        if sender.send(chunk).is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Score chunks until there are none left, returning the total score.
+/// On failure, returns the line number that failed along with the error.
+fn score_chunks<F>(
+    receiver: &Mutex<Receiver<Chunk>>,
+    failed: &AtomicBool,
+    score_line: &F,
+) -> Result<usize, (usize, Report)>
+where
+    F: Fn(usize, &str) -> Result<usize, Report>,
+{
+    let mut total = 0;
+    let mut error = None;
+    loop {
+        // Only one worker waits on the channel at a time, the rest wait on the lock.
+        let chunk = match receiver.lock() {
+            Ok(receiver) => receiver.recv(),
+            Err(_) => break,
+        };
+        let Ok(chunk) = chunk else { break };
+
+        // Once a line has failed, keep receiving chunks without scoring them,
+        // so that the reader is never left waiting for room in the channel.
+        if error.is_some() {
+            continue;
+        }
+
+        for (offset, line) in chunk.text.lines().enumerate() {
+            let number = chunk.first_line + offset;
+            match score_line(number, line) {
+                Ok(score) => total += score,
+                Err(err) => {
+                    failed.store(true, Ordering::Relaxed);
+                    error = Some((number, err));
+                    break;
+                }
+            }
+        }
+    }
+
+    match error {
+        Some(error) => Err(error),
+        None => Ok(total),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        day2::{part1, part2, INPUT, ROCK_PAPER_SCISSORS},
+        ParseError,
+    };
+
+    #[test]
+    fn reader() -> Result<(), Report> {
+        let rules = &*ROCK_PAPER_SCISSORS;
+        assert_eq!(part1_reader(INPUT.as_bytes(), rules)?, part1(INPUT)?);
+        assert_eq!(part2_reader(INPUT.as_bytes(), rules)?, part2(INPUT)?);
+        assert_eq!(part1_reader("A Y\r\nB X\r\nC Z".as_bytes(), rules)?, 15);
+        Ok(())
+    }
+
+    #[test]
+    fn parallel() -> Result<(), Report> {
+        let rules = &*ROCK_PAPER_SCISSORS;
+        let score_move = |number: usize, line: &str| score_move(rules, number, line);
+        let score_outcome = |number: usize, line: &str| score_outcome(rules, number, line);
+        for threads in 1..=4 {
+            let threads = NonZeroUsize::new(threads).expect("must be non-zero");
+            for chunk_lines in [1, 7, 1000, CHUNK_LINES] {
+                let score = score_parallel(INPUT.as_bytes(), threads, chunk_lines, score_move)?;
+                assert_eq!(score, 11386);
+                let score = score_parallel(INPUT.as_bytes(), threads, chunk_lines, score_outcome)?;
+                assert_eq!(score, 13600);
+            }
+        }
+
+        let threads = NonZeroUsize::new(2).expect("must be non-zero");
+        assert_eq!(part1_parallel(INPUT.as_bytes(), rules, threads)?, 11386);
+        assert_eq!(part2_parallel(INPUT.as_bytes(), rules, threads)?, 13600);
+        Ok(())
+    }
+
+    #[test]
+    fn parallel_error() {
+        let rules = &*ROCK_PAPER_SCISSORS;
+        let score_move = |number: usize, line: &str| score_move(rules, number, line);
+
+        // Whichever thread finishes first, the earliest error is reported.
+        let guide = "A Y\n".repeat(100) + "A Q\n" + &"A Y\n".repeat(100) + "A R\n";
+        let threads = NonZeroUsize::new(4).expect("must be non-zero");
+        for _ in 0..10 {
+            let err =
+                score_parallel(guide.as_bytes(), threads, 10, score_move).expect_err("must error");
+            let err = err
+                .downcast_ref::<ParseError>()
+                .expect("must be a parse error");
+            assert_eq!(err.line_number(), 101);
+            assert_eq!(err.text(), "Q");
+        }
+    }
+
+    #[test]
+    fn parallel_panic() {
+        // Far more chunks than fit in the channel, none of which can be scored.
+        let guide = "A Y\n".repeat(100);
+        let score_line = |_: usize, _: &str| -> Result<usize, Report> { panic!("scoring failed") };
+        for threads in [1, 3] {
+            let threads = NonZeroUsize::new(threads).expect("must be non-zero");
+            let err =
+                score_parallel(guide.as_bytes(), threads, 1, score_line).expect_err("must error");
+            assert!(format!("{err:?}").contains("a thread scoring the guide panicked"));
+        }
+    }
+}
@@ -0,0 +1,650 @@
+use std::{cmp::Reverse, collections::HashSet, fmt::Write};
+
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use stable_eyre::{
+    eyre::{ensure, Context},
+    Report,
+};
+
+use super::{
+    evaluate_round, parse::parse_rounds, parse::player_constraint, parse::player_move,
+    predict::best_response, reconstruct_rounds, round_score, GameRules, Predictor, Round, Shape,
+};
+
+/// How a tournament entrant chooses its moves over the course of a match.
+pub trait Strategy {
+    /// Choose the move for the next round.
+    fn choose(&mut self, rules: &GameRules, rng: &mut dyn RngCore) -> Shape;
+
+    /// Record the move the opponent made in the round that was just played.
+    fn observe(&mut self, opponent: Shape);
+}
+
+/// Plays a fixed sequence of moves, starting over when it runs out.
+#[derive(Debug, Clone)]
+pub struct FixedStrategy {
+    moves: Vec<Shape>,
+    next: usize,
+}
+
+impl FixedStrategy {
+    /// There must be at least one move to play.
+    pub fn new(moves: Vec<Shape>) -> Result<Self, Report> {
+        ensure!(!moves.is_empty(), "no moves to play");
+        Ok(Self { moves, next: 0 })
+    }
+}
+
+impl Strategy for FixedStrategy {
+    fn choose(&mut self, _: &GameRules, _: &mut dyn RngCore) -> Shape {
+        let shape = self.moves[self.next % self.moves.len()];
+        self.next += 1;
+        shape
+    }
+
+    fn observe(&mut self, _: Shape) {}
+}
+
+/// Throws a shape at random.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomStrategy;
+
+impl Strategy for RandomStrategy {
+    fn choose(&mut self, rules: &GameRules, rng: &mut dyn RngCore) -> Shape {
+        let index = rng.gen_range(0..rules.len());
+        rules.shapes().nth(index).expect("index must be in range")
+    }
+
+    fn observe(&mut self, _: Shape) {}
+}
+
+/// Plays the best response to the move a [`Predictor`] expects the opponent to make.
+pub struct AdaptiveStrategy<P> {
+    predictor: P,
+}
+
+impl<P: Predictor> AdaptiveStrategy<P> {
+    pub fn new(predictor: P) -> Self {
+        Self { predictor }
+    }
+}
+
+impl<P: Predictor> Strategy for AdaptiveStrategy<P> {
+    fn choose(&mut self, rules: &GameRules, _: &mut dyn RngCore) -> Shape {
+        best_response(rules, &self.predictor.predict(rules.len()))
+    }
+
+    fn observe(&mut self, opponent: Shape) {
+        self.predictor.observe(opponent);
+    }
+}
+
+/// A named competitor in a tournament.
+///
+/// Each match starts with a new strategy, so that nothing is remembered from earlier matches.
+pub struct Entrant {
+    name: String,
+    strategy: Box<dyn Fn() -> Box<dyn Strategy>>,
+}
+
+impl Entrant {
+    pub fn new(
+        name: impl Into<String>,
+        strategy: impl Fn() -> Box<dyn Strategy> + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            strategy: Box::new(strategy),
+        }
+    }
+
+    /// Play the moves from the second column of a strategy guide, read as in [`part1`](super::part1).
+    pub fn guide_moves(
+        name: impl Into<String>,
+        input: &str,
+        rules: &GameRules,
+    ) -> Result<Self, Report> {
+        let moves = parse_rounds(input, rules, player_move(rules))?
+            .into_iter()
+            .map(|(_, player)| player)
+            .collect::<Vec<_>>();
+        Self::fixed(name, moves)
+    }
+
+    /// Play the moves that end each round of a strategy guide as its second column says,
+    /// read as in [`part2`](super::part2). The moves are worked out against the opponent in the guide,
+    /// not the one actually being played.
+    pub fn guide_outcomes(
+        name: impl Into<String>,
+        input: &str,
+        rules: &GameRules,
+    ) -> Result<Self, Report> {
+        let rounds = parse_rounds(input, rules, player_constraint(rules))?;
+        let moves = reconstruct_rounds(rules, rounds)?
+            .into_iter()
+            .map(|(_, player)| player)
+            .collect::<Vec<_>>();
+        Self::fixed(name, moves)
+    }
+
+    /// Play a fixed sequence of moves, starting over when it runs out.
+    pub fn fixed(name: impl Into<String>, moves: Vec<Shape>) -> Result<Self, Report> {
+        let name = name.into();
+        let strategy = FixedStrategy::new(moves).wrap_err_with(|| format!("entrant '{name}'"))?;
+        Ok(Self::new(name, move || Box::new(strategy.clone())))
+    }
+
+    /// Throw a shape at random.
+    pub fn random(name: impl Into<String>) -> Self {
+        Self::new(name, || Box::new(RandomStrategy))
+    }
+
+    /// Play the best response to the move the predictor expects the opponent to make.
+    pub fn adaptive<P: Predictor + 'static>(
+        name: impl Into<String>,
+        predictor: impl Fn() -> P + 'static,
+    ) -> Self {
+        Self::new(name, move || Box::new(AdaptiveStrategy::new(predictor())))
+    }
+
+    /// The name of the entrant.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// How entrants are paired up for matches.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Pairing {
+    /// Every entrant plays every other entrant once.
+    RoundRobin,
+
+    /// Over the given number of rounds, entrants play others with a similar record that they haven't played yet.
+    /// If there's an odd number of entrants, the lowest ranked entrant that hasn't had a bye sits out each round,
+    /// and is awarded a win.
+    Swiss(usize),
+}
+
+/// The settings for a tournament.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Tournament {
+    /// How entrants are paired up for matches.
+    pub pairing: Pairing,
+
+    /// The number of rounds of the game played in each match.
+    pub rounds_per_match: usize,
+
+    /// The seed for the random number generator, so that a tournament can be replayed exactly.
+    pub seed: u64,
+}
+
+/// The points awarded for winning a match. A draw is worth half.
+const WIN_POINTS: usize = 2;
+
+impl Tournament {
+    /// Run the tournament between the entrants.
+    ///
+    /// The winner of a match is whoever scores the most over its rounds.
+    pub fn run(&self, rules: &GameRules, entrants: &[Entrant]) -> Result<TournamentResult, Report> {
+        ensure!(
+            entrants.len() >= 2,
+            "a tournament needs at least two entrants"
+        );
+        ensure!(self.rounds_per_match > 0, "matches need at least one round");
+
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+        let mut result = TournamentResult {
+            names: entrants
+                .iter()
+                .map(|entrant| entrant.name.clone())
+                .collect(),
+            standings: (0..entrants.len()).map(Standing::new).collect(),
+            matches: Vec::new(),
+        };
+
+        match self.pairing {
+            Pairing::RoundRobin => {
+                for first in 0..entrants.len() {
+                    for second in first + 1..entrants.len() {
+                        let played = self.play_match(rules, entrants, first, second, &mut rng);
+                        result.record(played);
+                    }
+                }
+            }
+            Pairing::Swiss(rounds) => {
+                let mut byes = HashSet::new();
+                for _ in 0..rounds {
+                    let (pairs, bye) = result.swiss_pairs(&byes);
+                    if let Some(bye) = bye {
+                        byes.insert(bye);
+                        result.standing_mut(bye).award_bye();
+                    }
+                    for (first, second) in pairs {
+                        let played = self.play_match(rules, entrants, first, second, &mut rng);
+                        result.record(played);
+                    }
+                }
+            }
+        }
+
+        result.sort_standings();
+        Ok(result)
+    }
+
+    /// Play a match between two entrants, identified by their position in `entrants`.
+    fn play_match(
+        &self,
+        rules: &GameRules,
+        entrants: &[Entrant],
+        first: usize,
+        second: usize,
+        rng: &mut ChaCha8Rng,
+    ) -> MatchResult {
+        let mut first_strategy = (entrants[first].strategy)();
+        let mut second_strategy = (entrants[second].strategy)();
+        let mut result = MatchResult {
+            first,
+            second,
+            first_score: 0,
+            second_score: 0,
+            first_wins: 0,
+            second_wins: 0,
+            draws: 0,
+        };
+
+        for _ in 0..self.rounds_per_match {
+            let first_move = first_strategy.choose(rules, rng);
+            let second_move = second_strategy.choose(rules, rng);
+            first_strategy.observe(second_move);
+            second_strategy.observe(first_move);
+
+            result.first_score += round_score(rules, second_move, first_move);
+            result.second_score += round_score(rules, first_move, second_move);
+            match evaluate_round(rules, second_move, first_move) {
+                Round::PlayerWin => result.first_wins += 1,
+                Round::Draw => result.draws += 1,
+                Round::PlayerLose => result.second_wins += 1,
+            }
+        }
+
+        result
+    }
+}
+
+/// The result of a match between two entrants, identified by their position in the list of entrants.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MatchResult {
+    pub first: usize,
+    pub second: usize,
+
+    /// The total score of each entrant over the match.
+    pub first_score: usize,
+    pub second_score: usize,
+
+    /// The number of rounds each entrant won, and the number of rounds that were drawn.
+    pub first_wins: usize,
+    pub second_wins: usize,
+    pub draws: usize,
+}
+
+impl MatchResult {
+    /// The outcome of the match from the point of view of the entrant, if they played in it.
+    pub fn outcome(&self, entrant: usize) -> Option<Round> {
+        let (own, other) = if entrant == self.first {
+            (self.first_score, self.second_score)
+        } else if entrant == self.second {
+            (self.second_score, self.first_score)
+        } else {
+            return None;
+        };
+
+        Some(match own.cmp(&other) {
+            std::cmp::Ordering::Greater => Round::PlayerWin,
+            std::cmp::Ordering::Equal => Round::Draw,
+            std::cmp::Ordering::Less => Round::PlayerLose,
+        })
+    }
+
+    /// The score the entrant earned in the match, if they played in it.
+    pub fn score(&self, entrant: usize) -> Option<usize> {
+        if entrant == self.first {
+            Some(self.first_score)
+        } else if entrant == self.second {
+            Some(self.second_score)
+        } else {
+            None
+        }
+    }
+}
+
+/// How an entrant has done over the tournament.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Standing {
+    /// The position of the entrant in the list of entrants.
+    pub entrant: usize,
+
+    /// Two points for each match won (including byes) and one for each match drawn.
+    pub points: usize,
+
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+
+    /// The entrant's total score over every round they played.
+    pub score: usize,
+}
+
+impl Standing {
+    fn new(entrant: usize) -> Self {
+        Self {
+            entrant,
+            points: 0,
+            wins: 0,
+            draws: 0,
+            losses: 0,
+            score: 0,
+        }
+    }
+
+    fn award_bye(&mut self) {
+        self.wins += 1;
+        self.points += WIN_POINTS;
+    }
+}
+
+/// The outcome of a tournament.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TournamentResult {
+    /// The name of each entrant, in the order they were entered.
+    pub names: Vec<String>,
+
+    /// How each entrant did, from first place to last.
+    /// Ties on points are broken by total score, and then by the order the entrants were entered.
+    pub standings: Vec<Standing>,
+
+    /// Every match, in the order they were played.
+    pub matches: Vec<MatchResult>,
+}
+
+impl TournamentResult {
+    /// The record of the entrant against the opponent: how many matches they won, drew and lost.
+    pub fn head_to_head(&self, entrant: usize, opponent: usize) -> (usize, usize, usize) {
+        self.matches
+            .iter()
+            .filter(|played| played.outcome(opponent).is_some())
+            .filter_map(|played| played.outcome(entrant))
+            .fold((0, 0, 0), |(wins, draws, losses), outcome| match outcome {
+                Round::PlayerWin => (wins + 1, draws, losses),
+                Round::Draw => (wins, draws + 1, losses),
+                Round::PlayerLose => (wins, draws, losses + 1),
+            })
+    }
+
+    /// The standings, followed by the head to head record of every entrant against every other.
+    pub fn table(&self) -> String {
+        let mut table = String::new();
+        let width = self.names.iter().map(String::len).max().unwrap_or_default();
+
+        table.push_str("standings:\n");
+        for (place, standing) in self.standings.iter().enumerate() {
+            writeln!(
+                table,
+                "{:>3}. {:<width$}  {:>3} points  {:>2}-{}-{}  {:>7} scored",
+                place + 1,
+                self.names[standing.entrant],
+                standing.points,
+                standing.wins,
+                standing.draws,
+                standing.losses,
+                standing.score,
+            )
+            .expect("write to string");
+        }
+
+        // Entrants are listed in the order they placed, and columns are numbered by place.
+        table.push_str("\nhead to head (wins-draws-losses against each place):\n");
+        write!(table, "{:>3}  {:<width$}", "", "").expect("write to string");
+        for place in 1..=self.standings.len() {
+            write!(table, "  {place:>5}").expect("write to string");
+        }
+        table.push('\n');
+        for (place, standing) in self.standings.iter().enumerate() {
+            write!(
+                table,
+                "{:>3}. {:<width$}",
+                place + 1,
+                self.names[standing.entrant]
+            )
+            .expect("write to string");
+            for opponent in &self.standings {
+                let cell = match self.head_to_head(standing.entrant, opponent.entrant) {
+                    _ if opponent.entrant == standing.entrant => String::from("-"),
+                    (0, 0, 0) => String::new(),
+                    (wins, draws, losses) => format!("{wins}-{draws}-{losses}"),
+                };
+                write!(table, "  {cell:>5}").expect("write to string");
+            }
+            table.push('\n');
+        }
+
+        table
+    }
+
+    /// Record the result of a match in the standings.
+    fn record(&mut self, played: MatchResult) {
+        for entrant in [played.first, played.second] {
+            let standing = self.standing_mut(entrant);
+            standing.score += played.score(entrant).unwrap_or_default();
+            match played.outcome(entrant) {
+                Some(Round::PlayerWin) => {
+                    standing.wins += 1;
+                    standing.points += WIN_POINTS;
+                }
+                Some(Round::Draw) => {
+                    standing.draws += 1;
+                    standing.points += WIN_POINTS / 2;
+                }
+                Some(Round::PlayerLose) => standing.losses += 1,
+                None => {}
+            }
+        }
+        self.matches.push(played);
+    }
+
+    /// The standing of the entrant. Standings are sorted as the tournament goes on,
+    /// so they can't be looked up by position.
+    fn standing_mut(&mut self, entrant: usize) -> &mut Standing {
+        self.standings
+            .iter_mut()
+            .find(|standing| standing.entrant == entrant)
+            .expect("every entrant must have a standing")
+    }
+
+    /// Sort the standings from first place to last.
+    fn sort_standings(&mut self) {
+        self.standings.sort_by_key(|standing| {
+            (
+                Reverse(standing.points),
+                Reverse(standing.score),
+                standing.entrant,
+            )
+        });
+    }
+
+    /// Pair up the entrants for the next round of a Swiss tournament.
+    ///
+    /// Working down from the top of the standings, each entrant is paired with the next highest entrant
+    /// they haven't played yet, or the next highest entrant if they've played everyone.
+    /// Returns the pairs, along with the entrant that sits out the round if there's an odd number of entrants.
+    fn swiss_pairs(&mut self, byes: &HashSet<usize>) -> (Vec<(usize, usize)>, Option<usize>) {
+        self.sort_standings();
+        let mut unpaired = self
+            .standings
+            .iter()
+            .map(|standing| standing.entrant)
+            .collect::<Vec<_>>();
+
+        let bye = if unpaired.len() % 2 == 1 {
+            let position = unpaired
+                .iter()
+                .rposition(|entrant| !byes.contains(entrant))
+                .unwrap_or(unpaired.len() - 1);
+            Some(unpaired.remove(position))
+        } else {
+            None
+        };
+
+        let played = |first: usize, second: usize| {
+            self.matches
+                .iter()
+                .any(|played| played.outcome(first).is_some() && played.outcome(second).is_some())
+        };
+
+        let mut pairs = Vec::with_capacity(unpaired.len() / 2);
+        while !unpaired.is_empty() {
+            let first = unpaired.remove(0);
+            let position = unpaired
+                .iter()
+                .position(|second| !played(first, *second))
+                .unwrap_or(0);
+            pairs.push((first, unpaired.remove(position)));
+        }
+
+        (pairs, bye)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day2::{MarkovPredictor, INPUT, ROCK_PAPER_SCISSORS};
+
+    fn entrants() -> Result<Vec<Entrant>, Report> {
+        let rules = &*ROCK_PAPER_SCISSORS;
+        Ok(vec![
+            Entrant::fixed("rock", vec![Shape::ROCK])?,
+            Entrant::fixed("paper", vec![Shape::PAPER])?,
+            Entrant::random("random"),
+            Entrant::adaptive("markov(1)", || MarkovPredictor::new(1)),
+            Entrant::guide_moves("guide as moves", INPUT, rules)?,
+            Entrant::guide_outcomes("guide as outcomes", INPUT, rules)?,
+        ])
+    }
+
+    #[test]
+    fn round_robin() -> Result<(), Report> {
+        let tournament = Tournament {
+            pairing: Pairing::RoundRobin,
+            rounds_per_match: 50,
+            seed: 2022,
+        };
+        let entrants = entrants()?;
+        let result = tournament.run(&ROCK_PAPER_SCISSORS, &entrants)?;
+        assert_eq!(result.matches.len(), 6 * 5 / 2);
+
+        // Paper beats Rock in every round of their match.
+        let (rock, paper) = (0, 1);
+        assert_eq!(result.head_to_head(paper, rock), (1, 0, 0));
+        assert_eq!(result.head_to_head(rock, paper), (0, 0, 1));
+        let played = result.matches[0];
+        assert_eq!((played.first_wins, played.second_wins), (0, 50));
+        assert_eq!((played.first_score, played.second_score), (50, 400));
+
+        // Always throwing the same shape is easy to predict.
+        let markov = 3;
+        assert_eq!(result.head_to_head(markov, rock), (1, 0, 0));
+        assert_eq!(result.head_to_head(markov, paper), (1, 0, 0));
+
+        let points = result
+            .standings
+            .iter()
+            .map(|standing| standing.points)
+            .sum::<usize>();
+        assert_eq!(points, result.matches.len() * WIN_POINTS);
+        Ok(())
+    }
+
+    #[test]
+    fn reproducible() -> Result<(), Report> {
+        let tournament = Tournament {
+            pairing: Pairing::Swiss(3),
+            rounds_per_match: 20,
+            seed: 7,
+        };
+        let entrants = entrants()?;
+        let first = tournament.run(&ROCK_PAPER_SCISSORS, &entrants)?;
+        let second = tournament.run(&ROCK_PAPER_SCISSORS, &entrants)?;
+        assert_eq!(first, second);
+        assert_eq!(first.table(), second.table());
+        Ok(())
+    }
+
+    #[test]
+    fn swiss() -> Result<(), Report> {
+        let tournament = Tournament {
+            pairing: Pairing::Swiss(3),
+            rounds_per_match: 20,
+            seed: 7,
+        };
+        let mut entrants = entrants()?;
+        entrants.push(Entrant::fixed("scissors", vec![Shape::SCISSORS])?);
+        let result = tournament.run(&ROCK_PAPER_SCISSORS, &entrants)?;
+
+        // With seven entrants, three matches are played each round and one entrant gets a bye.
+        assert_eq!(result.matches.len(), 9);
+        let matches = result
+            .standings
+            .iter()
+            .map(|standing| standing.wins + standing.draws + standing.losses)
+            .sum::<usize>();
+        assert_eq!(matches, 9 * 2 + 3);
+
+        // Each entrant's record covers the matches they played, plus a bye at most.
+        for standing in &result.standings {
+            let played = result
+                .matches
+                .iter()
+                .filter(|played| played.outcome(standing.entrant).is_some())
+                .count();
+            let recorded = standing.wins + standing.draws + standing.losses;
+            assert!(recorded == played || recorded == played + 1);
+        }
+
+        // Nobody plays the same opponent twice while there's someone else left to play.
+        let pairs = result
+            .matches
+            .iter()
+            .map(|played| {
+                (
+                    played.first.min(played.second),
+                    played.first.max(played.second),
+                )
+            })
+            .collect::<HashSet<_>>();
+        assert_eq!(pairs.len(), 9);
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_tournament() -> Result<(), Report> {
+        let tournament = Tournament {
+            pairing: Pairing::RoundRobin,
+            rounds_per_match: 10,
+            seed: 0,
+        };
+        let rock = || Entrant::fixed("rock", vec![Shape::ROCK]);
+        assert!(tournament.run(&ROCK_PAPER_SCISSORS, &[rock()?]).is_err());
+        assert!(Entrant::fixed("nothing", vec![]).is_err());
+        assert!(FixedStrategy::new(vec![]).is_err());
+
+        let tournament = Tournament {
+            rounds_per_match: 0,
+            ..tournament
+        };
+        assert!(tournament
+            .run(&ROCK_PAPER_SCISSORS, &[rock()?, rock()?])
+            .is_err());
+        Ok(())
+    }
+}
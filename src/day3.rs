@@ -1,15 +1,55 @@
-use std::collections::{HashMap, HashSet};
-
 use itertools::Itertools;
-use once_cell::sync::Lazy;
 use stable_eyre::{
     eyre::{bail, ensure, Context},
     Report,
 };
 
+use crate::{Answer, Solution};
+
+mod diagnose;
+mod groups;
+mod inventory;
+mod items;
+mod layout;
+mod priority;
+mod rebalance;
+
+pub use diagnose::{diagnose, Diagnosis, Finding, Status};
+pub use groups::{discover_groups, BadgeGroup, Partition};
+pub use inventory::Inventory;
+pub use items::{ItemIndex, ItemSet};
+pub use layout::Layout;
+pub use priority::{Alphabet, PriorityScheme, DIGITS, LETTERS};
+pub use rebalance::{rebalance, Move, RebalancePlan, RucksackPlan, Side};
+
 /// The puzzle input.
 pub const INPUT: &str = include_str!("input/day3");
 
+/// The solution for day 3.
+pub struct Day3;
+
+impl Solution for Day3 {
+    fn day(&self) -> u8 {
+        3
+    }
+
+    fn title(&self) -> &'static str {
+        "Rucksack Reorganization"
+    }
+
+    fn input(&self) -> &'static str {
+        INPUT
+    }
+
+    fn part1(&self, input: &str) -> Result<Answer, Report> {
+        part1(input).map(Answer::from)
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, Report> {
+        part2(input).map(Answer::from)
+    }
+}
+
 /// The list of items for each rucksack is given as characters all on a single line.
 /// A given rucksack always has the same number of items in each of its two compartments,
 /// so the first half of the characters represent items in the first compartment,
@@ -48,9 +88,29 @@ pub const INPUT: &str = include_str!("input/day3");
 /// Find the item type that appears in both compartments of each rucksack.
 /// What is the sum of the priorities of those item types?
 pub fn part1(input: &str) -> Result<usize, Report> {
+    part1_with(input, &*LETTERS)
+}
+
+/// Solve [`part1`] with item types converted to priorities by a different scheme.
+pub fn part1_with(input: &str, scheme: &dyn PriorityScheme) -> Result<usize, Report> {
+    part1_with_layout(input, scheme, &Layout::default())
+}
+
+/// Solve [`part1`] with a different scheme, for rucksacks with a different number of compartments.
+/// The item type of a rucksack is then the one in every compartment.
+pub fn part1_with_layout(
+    input: &str,
+    scheme: &dyn PriorityScheme,
+    layout: &Layout,
+) -> Result<usize, Report> {
+    let mut items = ItemIndex::new();
     input
         .lines()
-        .map(Rucksack::parse)
+        .enumerate()
+        .map(|(index, line)| {
+            Rucksack::parse(line, scheme, layout, &mut items)
+                .wrap_err_with(|| format!("parse rucksack on line {}", index + 1))
+        })
         .collect::<Result<Vec<_>, _>>()
         .map(score_rucksacks)
 }
@@ -81,34 +141,60 @@ pub fn part1(input: &str) -> Result<usize, Report> {
 ///
 /// Find the item type that corresponds to the badges of each three-Elf group.
 pub fn part2(input: &str) -> Result<usize, Report> {
-    input
+    part2_with(input, &*LETTERS)
+}
+
+/// Solve [`part2`] with item types converted to priorities by a different scheme.
+pub fn part2_with(input: &str, scheme: &dyn PriorityScheme) -> Result<usize, Report> {
+    part2_with_layout(input, scheme, &Layout::default())
+}
+
+/// Solve [`part2`] with a different scheme, for groups of a different size
+/// made up of rucksacks with a different number of compartments.
+pub fn part2_with_layout(
+    input: &str,
+    scheme: &dyn PriorityScheme,
+    layout: &Layout,
+) -> Result<usize, Report> {
+    let mut items = ItemIndex::new();
+    let groups = input
         .lines()
-        .chunks(3)
+        .chunks(layout.group_size())
         .into_iter()
-        .map(Group::parse)
-        .collect::<Result<Vec<_>, _>>()
-        .and_then(score_groups)
+        .enumerate()
+        .map(|(index, lines)| {
+            Group::parse(lines, scheme, layout, &mut items)
+                .wrap_err_with(|| format!("parse group {}", index + 1))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    score_groups(groups, scheme, &items)
 }
 
 fn score_rucksacks(sacks: Vec<Rucksack>) -> usize {
     sacks.into_iter().map(|rs| rs.priority).sum()
 }
 
-fn score_groups(groups: Vec<Group>) -> Result<usize, Report> {
+fn score_groups(
+    groups: Vec<Group>,
+    scheme: &dyn PriorityScheme,
+    items: &ItemIndex,
+) -> Result<usize, Report> {
     groups
         .iter()
-        .map(Group::score)
+        .enumerate()
+        .map(|(index, group)| {
+            group
+                .score(scheme, items)
+                .wrap_err_with(|| format!("score group {}", index + 1))
+        })
         .collect::<Result<Vec<_>, _>>()
         .map(|scores| scores.into_iter().sum())
 }
 
-/// Every item type can be converted to a priority:
-///
-/// - Lowercase item types a through z have priorities 1 through 26.
-/// - Uppercase item types A through Z have priorities 27 through 52.
-fn calculate_priority(item_type: &char) -> Result<usize, Report> {
-    let Some(priority) = PRIORITY.get(item_type) else { bail!("no priority for item type") };
-    Ok(priority.to_owned())
+/// Every item type can be converted to a priority, as set out by the scheme.
+fn calculate_priority(scheme: &dyn PriorityScheme, item_type: &char) -> Result<usize, Report> {
+    let Some(priority) = scheme.priority(*item_type) else { bail!("no priority for item type {item_type:?}") };
+    Ok(priority)
 }
 
 /// A group is made up of multiple rucksacks.
@@ -121,116 +207,169 @@ impl Group {
         Self { rucksacks }
     }
 
-    fn parse<'a>(lines: impl IntoIterator<Item = &'a str>) -> Result<Self, Report> {
-        lines
+    /// Every group must have exactly as many rucksacks as the layout calls for.
+    fn parse<'a>(
+        lines: impl IntoIterator<Item = &'a str>,
+        scheme: &dyn PriorityScheme,
+        layout: &Layout,
+        items: &mut ItemIndex,
+    ) -> Result<Self, Report> {
+        let rucksacks = lines
             .into_iter()
-            .map(Rucksack::parse)
-            .collect::<Result<Vec<_>, _>>()
-            .map(Self::new)
-    }
-
-    fn score(&self) -> Result<usize, Report> {
-        let mut intersections = self
-            .rucksacks
-            .iter()
-            .fold(HashSet::new(), |mut acc, sack| {
-                let contents = sack.contents.chars().collect::<HashSet<_>>();
-                let matching = |c: &char| contents.contains(c);
-                if acc.is_empty() {
-                    contents
-                } else {
-                    acc.retain(matching);
-                    acc
-                }
-            })
-            .into_iter();
+            .map(|line| Rucksack::parse(line, scheme, layout, items))
+            .collect::<Result<Vec<_>, _>>()?;
+        ensure!(
+            rucksacks.len() == layout.group_size(),
+            "expected {} rucksacks in the group, got {}",
+            layout.group_size(),
+            rucksacks.len()
+        );
+        Ok(Self::new(rucksacks))
+    }
 
+    /// The item types in every rucksack of the group.
+    fn shared(&self) -> ItemSet {
+        shared_items(self.rucksacks.iter().map(|sack| &sack.items))
+    }
+
+    /// The badge is the only item type carried by every rucksack in the group.
+    fn badge(&self, items: &ItemIndex) -> Result<char, Report> {
+        let shared = self.shared();
+        let mut intersections = shared.iter();
         let Some(group_type) = intersections.next() else { bail!("no intersections found"); };
         ensure!(
             intersections.next().is_none(),
             "more than one intersection found"
         );
+        Ok(items
+            .item(group_type)
+            .expect("item sets only hold indexed item types"))
+    }
 
-        calculate_priority(&group_type)
+    fn score(&self, scheme: &dyn PriorityScheme, items: &ItemIndex) -> Result<usize, Report> {
+        let group_type = self.badge(items)?;
+        calculate_priority(scheme, &group_type)
+            .wrap_err_with(|| format!("calculate_priority({group_type:?})"))
     }
 }
 
-/// A rucksack is made up of two compartments.
+/// A rucksack is made up of compartments, usually two.
 ///
-/// The item type that is shared between the two compartments is the item type of the rucksack.
+/// The item type that is shared between the compartments is the item type of the rucksack.
 /// The priority is then based upon that item type.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Rucksack {
-    contents: String,
+    items: ItemSet,
     priority: usize,
 }
 
 impl Rucksack {
-    /// A rucksack is made up of two compartments.
+    /// A rucksack is made up of as many compartments as the layout calls for.
     ///
-    /// The input string is split evenly in half, and the two halves are both compartments.
-    fn parse(input: &str) -> Result<Self, Report> {
-        let delimiter = input.len() / 2;
-        let (first, second) = input.split_at(delimiter);
-        let (first, second) = (Compartment::parse(first), Compartment::parse(second));
-
-        let item_type = Self::calculate_item_type(&first, &second)
-            .wrap_err_with(|| format!("calculate_item_type({first:?}, {second:?})"))?;
-        let priority = calculate_priority(&item_type)
+    /// The input string is split evenly into compartments, and it's an error if it can't be.
+    /// Item types are recorded in `items` as they're seen, so that rucksacks parsed with the same index can be compared.
+    fn parse(
+        input: &str,
+        scheme: &dyn PriorityScheme,
+        layout: &Layout,
+        items: &mut ItemIndex,
+    ) -> Result<Self, Report> {
+        let compartments = Self::compartments(input, layout, items)?;
+        let item_type = Self::calculate_item_type(&compartments, items)
+            .wrap_err_with(|| format!("calculate_item_type({compartments:?})"))?;
+        let priority = calculate_priority(scheme, &item_type)
             .wrap_err_with(|| format!("calculate_priority({item_type:?})"))?;
 
         Ok(Self {
-            contents: input.to_owned(),
+            items: compartments
+                .iter()
+                .fold(ItemSet::new(), |all, compartment| {
+                    all.union(&compartment.contents)
+                }),
             priority,
         })
     }
 
-    /// The item type that is shared between the two compartments is the item type of the rucksack.
-    fn calculate_item_type(first: &Compartment, second: &Compartment) -> Result<char, Report> {
-        let mut intersections = first.contents.intersection(&second.contents);
+    /// Split the input evenly into as many compartments as the layout calls for, recording their item types in `items`.
+    fn compartments(
+        input: &str,
+        layout: &Layout,
+        items: &mut ItemIndex,
+    ) -> Result<Vec<Compartment>, Report> {
+        let compartments = split_compartments(input, layout)?
+            .into_iter()
+            .map(|compartment| Compartment::parse(compartment, items))
+            .collect();
+        Ok(compartments)
+    }
+
+    /// The item type that is shared between the compartments is the item type of the rucksack.
+    fn calculate_item_type(
+        compartments: &[Compartment],
+        items: &ItemIndex,
+    ) -> Result<char, Report> {
+        let shared = shared_items(compartments.iter().map(|compartment| &compartment.contents));
+        let mut intersections = shared.iter();
         let Some(intersection) = intersections.next() else { bail!("no intersection found"); };
         ensure!(
             intersections.next().is_none(),
             "more than one intersection found"
         );
-        Ok(intersection.to_owned())
+        Ok(items
+            .item(intersection)
+            .expect("item sets only hold indexed item types"))
+    }
+}
+
+/// The item types in every one of the sets, or none if there are no sets.
+fn shared_items<'a>(sets: impl IntoIterator<Item = &'a ItemSet>) -> ItemSet {
+    let mut sets = sets.into_iter();
+    let first = sets.next().cloned().unwrap_or_default();
+    sets.fold(first, |shared, set| shared.intersection(set))
+}
+
+/// Split the items in a rucksack evenly into as many compartments as the layout calls for.
+/// Item types may take up more than one byte, so the items are counted rather than the bytes.
+fn split_compartments<'a>(input: &'a str, layout: &Layout) -> Result<Vec<&'a str>, Report> {
+    let items = input.chars().count();
+    ensure!(
+        items.is_multiple_of(layout.compartments()),
+        "{items} items can't be split evenly between {} compartments",
+        layout.compartments()
+    );
+
+    let size = items / layout.compartments();
+    let mut compartments = Vec::with_capacity(layout.compartments());
+    let mut rest = input;
+    for _ in 0..layout.compartments() {
+        let delimiter = rest
+            .char_indices()
+            .nth(size)
+            .map_or(rest.len(), |(index, _)| index);
+        let (compartment, remaining) = rest.split_at(delimiter);
+        compartments.push(compartment);
+        rest = remaining;
     }
+    Ok(compartments)
 }
 
 #[derive(Debug)]
 struct Compartment {
-    contents: HashSet<char>,
+    contents: ItemSet,
 }
 
 impl Compartment {
-    fn parse(input: &str) -> Self {
+    fn parse(input: &str, items: &mut ItemIndex) -> Self {
         Self {
-            contents: input.chars().collect(),
+            contents: ItemSet::parse(input, items),
         }
     }
 }
 
-static PRIORITY: Lazy<HashMap<char, usize>> = Lazy::new(|| {
-    let lower = 'a'..='z';
-    let upper = 'A'..='Z';
-    let flip = |(i, c): (usize, char)| (c, i);
-    let idx_to_count = |(c, i): (char, usize)| (c, i + 1);
-    HashMap::from_iter(lower.chain(upper).enumerate().map(flip).map(idx_to_count))
-});
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn priority_correct() {
-        assert_eq!(PRIORITY[&'a'], 1);
-        assert_eq!(PRIORITY[&'b'], 2);
-        assert_eq!(PRIORITY[&'z'], 26);
-        assert_eq!(PRIORITY[&'A'], 27);
-        assert_eq!(PRIORITY[&'Z'], 52);
-    }
-
     #[test]
     fn test_part1() -> Result<(), Report> {
         assert_eq!(part1(INPUT)?, 7875);
@@ -242,4 +381,66 @@ mod tests {
         assert_eq!(part2(INPUT)?, 2479);
         Ok(())
     }
+
+    #[test]
+    fn unicode_items() -> Result<(), Report> {
+        // The rucksacks' compartments share 'β', '🍎' and 'α', and the group shares 'γ'.
+        let alphabet = Alphabet::parse("αβγδ🍎")?;
+        let input = "αγβδβ🍎\nγ🍎β🍎\nγαδα\n";
+        assert_eq!(part1_with(input, &alphabet)?, 2 + 5 + 1);
+        assert_eq!(part2_with(input, &alphabet)?, 3);
+
+        let err = part1_with("xx", &alphabet).expect_err("must error");
+        assert!(format!("{err:?}").contains("no priority for item type 'x'"));
+        Ok(())
+    }
+
+    #[test]
+    fn many_item_types() -> Result<(), Report> {
+        // Seventy item types, so the shared ones have priorities well past 63.
+        let types = ('一'..).take(70).collect::<Vec<_>>();
+        let alphabet = Alphabet::new(types.iter().copied())?;
+        let line = |items: [usize; 4]| items.iter().map(|&item| types[item]).collect::<String>();
+        let input = [
+            line([0, 69, 69, 1]),
+            line([69, 2, 3, 2]),
+            line([66, 69, 4, 66]),
+        ]
+        .join("\n");
+        assert_eq!(part1_with(&input, &alphabet)?, 70 + 3 + 67);
+        assert_eq!(part2_with(&input, &alphabet)?, 70);
+        Ok(())
+    }
+
+    #[test]
+    fn duplicate_priorities() {
+        // Item types that share a priority are still different item types.
+        let scheme = |_| Some(1);
+        let err = part1_with("ac", &scheme).expect_err("must error");
+        assert!(format!("{err:?}").contains("no intersection found"));
+        let err = part2_with("aa\nbb\ncc\n", &scheme).expect_err("must error");
+        assert!(format!("{err:?}").contains("no intersections found"));
+    }
+
+    #[test]
+    fn layouts() -> Result<(), Report> {
+        // Three compartments of three items, which only all share 'a'.
+        let thirds = Layout::new(3, 2)?;
+        assert_eq!(part1_with_layout("abcadeafg", &*LETTERS, &thirds)?, 1);
+
+        // Groups of two, sharing 'a'; and groups of four, which this inventory doesn't have enough rucksacks for.
+        let pairs = Layout::new(2, 2)?;
+        assert_eq!(part2_with_layout("aa\nabcb\n", &*LETTERS, &pairs)?, 1);
+        let fours = Layout::new(2, 4)?;
+        let err = part2_with_layout("aa\nabcb\n", &*LETTERS, &fours).expect_err("must error");
+        assert!(format!("{err:?}").contains("expected 4 rucksacks in the group, got 2"));
+
+        // Odd numbers of items used to be split unevenly.
+        let err = part1("abcab").expect_err("must error");
+        assert!(format!("{err:?}").contains("5 items can't be split evenly between 2 compartments"));
+
+        assert!(Layout::new(1, 3).is_err());
+        assert!(Layout::new(2, 0).is_err());
+        Ok(())
+    }
 }
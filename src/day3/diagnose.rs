@@ -0,0 +1,219 @@
+use std::fmt;
+
+use itertools::Itertools;
+
+use super::{
+    calculate_priority, shared_items, ItemIndex, ItemSet, Layout, PriorityScheme, Rucksack,
+};
+
+/// What was found when looking for the item type shared by a rucksack's compartments, or by a group's rucksacks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    /// Exactly one item type is shared, as expected.
+    Valid { item: char, priority: usize },
+
+    /// No item type is shared.
+    Empty,
+
+    /// More than one item type is shared, so it's not clear which one is meant.
+    Ambiguous(Vec<char>),
+
+    /// The line or lines couldn't be read at all, e.g. because the items can't be split evenly.
+    Malformed(String),
+}
+
+impl Status {
+    /// The priority of the shared item type, if there's exactly one.
+    pub fn priority(&self) -> Option<usize> {
+        match self {
+            Status::Valid { priority, .. } => Some(*priority),
+            _ => None,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        matches!(self, Status::Valid { .. })
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Valid { item, priority } => write!(f, "shares {item:?} ({priority})"),
+            Status::Empty => write!(f, "shares no item type"),
+            Status::Ambiguous(items) => {
+                let items = items.iter().map(|item| format!("{item:?}")).join(", ");
+                write!(f, "shares more than one item type: {items}")
+            }
+            Status::Malformed(reason) => write!(f, "malformed: {reason}"),
+        }
+    }
+}
+
+/// The status of a rucksack, or a group of rucksacks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    /// The lines of the inventory the rucksacks are on.
+    pub lines: Vec<usize>,
+    pub status: Status,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.lines.as_slice() {
+            [line] => write!(f, "line {line}: {}", self.status),
+            lines => write!(f, "lines {}: {}", lines.iter().join(", "), self.status),
+        }
+    }
+}
+
+/// The status of every rucksack and every group in an inventory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnosis {
+    pub rucksacks: Vec<Finding>,
+    pub groups: Vec<Finding>,
+}
+
+impl Diagnosis {
+    /// The sum of the priorities of the rucksacks that are valid, as in [`part1`](super::part1).
+    pub fn part1(&self) -> usize {
+        self.rucksacks
+            .iter()
+            .filter_map(|r| r.status.priority())
+            .sum()
+    }
+
+    /// The sum of the priorities of the groups that are valid, as in [`part2`](super::part2).
+    pub fn part2(&self) -> usize {
+        self.groups.iter().filter_map(|g| g.status.priority()).sum()
+    }
+
+    /// Every rucksack, and then every group, that isn't valid.
+    pub fn problems(&self) -> impl Iterator<Item = &Finding> {
+        self.rucksacks
+            .iter()
+            .chain(&self.groups)
+            .filter(|finding| !finding.status.is_valid())
+    }
+}
+
+/// Look at every rucksack and every group in the inventory, recording what each of them shares
+/// instead of stopping at the first one that doesn't share exactly one item type.
+///
+/// Problems with a rucksack's compartments don't affect its group, since the group only looks at
+/// which item types are in each rucksack.
+///
+/// Shared item types are found the same way as in [`part1`](super::part1) and [`part2`](super::part2),
+/// and listed in the order they first appear in the inventory.
+pub fn diagnose(input: &str, scheme: &dyn PriorityScheme, layout: &Layout) -> Diagnosis {
+    let mut items = ItemIndex::new();
+    let mut contents = Vec::new();
+    let mut rucksacks = Vec::new();
+    for (index, line) in input.lines().enumerate() {
+        contents.push(ItemSet::parse(line, &mut items));
+        let status = match Rucksack::compartments(line, layout, &mut items) {
+            Ok(compartments) => {
+                let shared =
+                    shared_items(compartments.iter().map(|compartment| &compartment.contents));
+                shared_status(&shared, &items, scheme)
+            }
+            Err(err) => Status::Malformed(format!("{err:#}")),
+        };
+        rucksacks.push(Finding {
+            lines: vec![index + 1],
+            status,
+        });
+    }
+
+    let groups = contents
+        .chunks(layout.group_size())
+        .enumerate()
+        .map(|(index, members)| {
+            let first = index * layout.group_size() + 1;
+            let status = if members.len() == layout.group_size() {
+                shared_status(&shared_items(members), &items, scheme)
+            } else {
+                Status::Malformed(format!(
+                    "expected {} rucksacks in the group, got {}",
+                    layout.group_size(),
+                    members.len()
+                ))
+            };
+            Finding {
+                lines: (first..first + members.len()).collect(),
+                status,
+            }
+        })
+        .collect();
+
+    Diagnosis { rucksacks, groups }
+}
+
+/// The status of a rucksack or group that shares the item types in `shared`.
+fn shared_status(shared: &ItemSet, items: &ItemIndex, scheme: &dyn PriorityScheme) -> Status {
+    let shared = shared
+        .iter()
+        .map(|index| {
+            items
+                .item(index)
+                .expect("item sets only hold indexed item types")
+        })
+        .collect::<Vec<_>>();
+
+    match shared.as_slice() {
+        [] => Status::Empty,
+        [item] => match calculate_priority(scheme, item) {
+            Ok(priority) => Status::Valid {
+                item: *item,
+                priority,
+            },
+            Err(err) => Status::Malformed(format!("{err:#}")),
+        },
+        _ => Status::Ambiguous(shared),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day3::{part1, part2, INPUT, LETTERS};
+
+    #[test]
+    fn malformed() {
+        let input = "vJrwpWtwJgWrhcsFMMfFFhFp\nabcd\nabab\nabc\n1b1c\nab\n";
+        let diagnosis = diagnose(input, &*LETTERS, &Layout::default());
+
+        let problems = diagnosis
+            .problems()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            problems,
+            vec![
+                "line 2: shares no item type",
+                "line 3: shares more than one item type: 'a', 'b'",
+                "line 4: malformed: 3 items can't be split evenly between 2 compartments",
+                "line 5: malformed: no priority for item type '1'",
+                "line 6: shares no item type",
+                "lines 1, 2, 3: shares no item type",
+            ]
+        );
+
+        // The one valid rucksack and the one valid group are still scored.
+        assert_eq!(
+            diagnosis.groups[1].to_string(),
+            "lines 4, 5, 6: shares 'b' (2)"
+        );
+        assert_eq!(diagnosis.part1(), 16);
+        assert_eq!(diagnosis.part2(), 2);
+    }
+
+    #[test]
+    fn input() -> Result<(), stable_eyre::Report> {
+        let diagnosis = diagnose(INPUT, &*LETTERS, &Layout::default());
+        assert_eq!(diagnosis.problems().count(), 0);
+        assert_eq!(diagnosis.part1(), part1(INPUT)?);
+        assert_eq!(diagnosis.part2(), part2(INPUT)?);
+        Ok(())
+    }
+}
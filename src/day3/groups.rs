@@ -0,0 +1,393 @@
+use stable_eyre::{
+    eyre::{bail, ensure, Context},
+    Report,
+};
+
+use super::{Group, ItemIndex, ItemSet, Layout, PriorityScheme, Rucksack};
+
+/// A group of elves found by [`discover_groups`], and the badge they share.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BadgeGroup {
+    /// The lines of the inventory the group's rucksacks are on.
+    pub lines: Vec<usize>,
+
+    /// The item type that's in every rucksack of the group.
+    pub badge: char,
+
+    /// The priority of the badge.
+    pub priority: usize,
+}
+
+/// A way to split every rucksack in an inventory into groups.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Partition {
+    pub groups: Vec<BadgeGroup>,
+}
+
+impl Partition {
+    /// The sum of the priorities of every group's badge, as in [`part2`](super::part2).
+    pub fn score(&self) -> usize {
+        self.groups.iter().map(|group| group.priority).sum()
+    }
+}
+
+/// Split the rucksacks in an inventory, in any order, into groups of the size the layout calls for,
+/// where every group shares exactly one item type: its badge.
+///
+/// Up to `limit` partitions are returned, in the order they're found; `limit` must be at least one.
+/// Each partition lists its groups in the order of their first rucksack.
+/// If there's no way to split the rucksacks, an error explains why.
+///
+/// Every group that shares exactly one item type is considered, so this can take a lot of time and memory
+/// for large groups or large inventories.
+pub fn discover_groups(
+    input: &str,
+    scheme: &dyn PriorityScheme,
+    layout: &Layout,
+    limit: usize,
+) -> Result<Vec<Partition>, Report> {
+    ensure!(limit > 0, "at least one partition must be asked for");
+    let size = layout.group_size();
+    let mut items = ItemIndex::new();
+    let rucksacks = input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| {
+            Rucksack::parse(line, scheme, layout, &mut items)
+                .wrap_err_with(|| format!("parse rucksack on line {}", index + 1))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    ensure!(
+        rucksacks.len() % size == 0,
+        "{} rucksacks can't be split into groups of {size}",
+        rucksacks.len()
+    );
+
+    let mut search = Search::new(&rucksacks, size, limit);
+    if let Some(index) = search.live.iter().position(|live| *live == 0) {
+        bail!(
+            "the rucksack on line {} doesn't share exactly one item type with any group of {size}",
+            index + 1
+        );
+    }
+    search.partition();
+
+    if search.found.is_empty() {
+        bail!(
+            "no way to split the {} rucksacks into groups of {size} that each share exactly one item type",
+            rucksacks.len()
+        );
+    }
+
+    search
+        .found
+        .into_iter()
+        .map(|groups| {
+            let groups = groups
+                .into_iter()
+                .map(|members| badge_group(&rucksacks, scheme, &items, members))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Partition { groups })
+        })
+        .collect()
+}
+
+/// Describe the group made up of the rucksacks at the given indices.
+fn badge_group(
+    rucksacks: &[Rucksack],
+    scheme: &dyn PriorityScheme,
+    items: &ItemIndex,
+    members: Vec<usize>,
+) -> Result<BadgeGroup, Report> {
+    let group = Group::new(
+        members
+            .iter()
+            .map(|&index| rucksacks[index].clone())
+            .collect(),
+    );
+    let badge = group.badge(items)?;
+    let priority = group.score(scheme, items)?;
+
+    Ok(BadgeGroup {
+        lines: members.into_iter().map(|index| index + 1).collect(),
+        badge,
+        priority,
+    })
+}
+
+/// The state of the search for partitions.
+///
+/// Every group that could be part of a partition is found up front. Finding a partition is then
+/// a matter of picking groups that cover every rucksack exactly once, and the rucksack with the fewest
+/// groups left to pick from is always covered next, so that dead ends are found as early as possible.
+struct Search {
+    size: usize,
+    limit: usize,
+
+    /// Every group of `size` rucksacks that shares exactly one item type, as indices of rucksacks.
+    candidates: Vec<Vec<usize>>,
+
+    /// The candidates each rucksack is in.
+    memberships: Vec<Vec<usize>>,
+
+    /// How many rucksacks in each candidate are already in a group; candidates can only be picked while this is zero.
+    blocked: Vec<usize>,
+
+    /// How many candidates each rucksack is in that can still be picked.
+    live: Vec<usize>,
+
+    assigned: Vec<bool>,
+
+    /// The candidates picked for the partition being built.
+    picked: Vec<usize>,
+
+    found: Vec<Vec<Vec<usize>>>,
+}
+
+impl Search {
+    fn new(rucksacks: &[Rucksack], size: usize, limit: usize) -> Self {
+        let mut candidates = Vec::new();
+        find_candidates(
+            rucksacks,
+            size,
+            &mut Vec::new(),
+            ItemSet::new(),
+            0,
+            &mut candidates,
+        );
+
+        let mut memberships = vec![Vec::new(); rucksacks.len()];
+        for (candidate, members) in candidates.iter().enumerate() {
+            for member in members {
+                memberships[*member].push(candidate);
+            }
+        }
+
+        Self {
+            size,
+            limit,
+            blocked: vec![0; candidates.len()],
+            live: memberships.iter().map(Vec::len).collect(),
+            candidates,
+            memberships,
+            assigned: vec![false; rucksacks.len()],
+            picked: Vec::new(),
+            found: Vec::new(),
+        }
+    }
+
+    /// Pick candidates until every rucksack is in a group, then backtrack to try the others,
+    /// until `limit` partitions are found or there's nothing left to try.
+    ///
+    /// The search goes one level deeper for every group, so it keeps its own stack rather than recursing.
+    fn partition(&mut self) {
+        // For each rucksack being put in a group: the candidates it could go in, and how many have been tried.
+        // The candidate being tried is the matching entry in `picked`.
+        let mut stack = Vec::new();
+        self.cover_next(&mut stack);
+        while let Some((options, tried)) = stack.last_mut() {
+            if *tried > 0 {
+                self.pick(options[*tried - 1], false);
+            }
+            if *tried == options.len() || self.found.len() >= self.limit {
+                stack.pop();
+                continue;
+            }
+
+            let candidate = options[*tried];
+            *tried += 1;
+            self.pick(candidate, true);
+            self.cover_next(&mut stack);
+        }
+    }
+
+    /// Push the candidates for the rucksack with the fewest left onto the stack,
+    /// or record the partition if every rucksack is already in a group.
+    fn cover_next(&mut self, stack: &mut Vec<(Vec<usize>, usize)>) {
+        let next = (0..self.assigned.len())
+            .filter(|rucksack| !self.assigned[*rucksack])
+            .min_by_key(|rucksack| self.live[*rucksack]);
+        let Some(next) = next else {
+            let mut groups = self
+                .picked
+                .iter()
+                .map(|candidate| self.candidates[*candidate].clone())
+                .collect::<Vec<_>>();
+            groups.sort();
+            self.found.push(groups);
+            return;
+        };
+
+        let options = self.memberships[next]
+            .iter()
+            .copied()
+            .filter(|candidate| self.blocked[*candidate] == 0)
+            .collect::<Vec<_>>();
+        stack.push((options, 0));
+    }
+
+    /// Put the rucksacks in the candidate into a group, blocking every other candidate they're in;
+    /// or, if not `picking`, undo that.
+    fn pick(&mut self, candidate: usize, picking: bool) {
+        if picking {
+            self.picked.push(candidate);
+        } else {
+            self.picked.pop();
+        }
+
+        for index in 0..self.size {
+            let member = self.candidates[candidate][index];
+            self.assigned[member] = picking;
+            for &other in &self.memberships[member] {
+                // A candidate stops or starts counting towards its rucksacks' live candidates
+                // when its first rucksack is put in a group, or when its last one is taken back out.
+                if picking {
+                    self.blocked[other] += 1;
+                }
+                if self.blocked[other] == 1 {
+                    for &rucksack in &self.candidates[other] {
+                        if picking {
+                            self.live[rucksack] -= 1;
+                        } else {
+                            self.live[rucksack] += 1;
+                        }
+                    }
+                }
+                if !picking {
+                    self.blocked[other] -= 1;
+                }
+            }
+        }
+    }
+}
+
+/// Find every group of `size` rucksacks after `start` that shares exactly one item type,
+/// skipping any that stop sharing an item type partway through.
+fn find_candidates(
+    rucksacks: &[Rucksack],
+    size: usize,
+    members: &mut Vec<usize>,
+    shared: ItemSet,
+    start: usize,
+    candidates: &mut Vec<Vec<usize>>,
+) {
+    if members.len() == size {
+        if shared.len() == 1 {
+            candidates.push(members.clone());
+        }
+        return;
+    }
+
+    for next in start..rucksacks.len() {
+        let items = &rucksacks[next].items;
+        let shared = if members.is_empty() {
+            items.clone()
+        } else {
+            shared.intersection(items)
+        };
+        if shared.is_empty() {
+            continue;
+        }
+
+        members.push(next);
+        find_candidates(rucksacks, size, members, shared, next + 1, candidates);
+        members.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day3::{INPUT, LETTERS};
+
+    const EXAMPLE: &str = "vJrwpWtwJgWrhcsFMMfFFhFp
+jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+PmmdzqPrVvPwwTWBwg
+wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
+ttgJtRGJQctTZtZT
+CrZsJsPPZsGzwwsLwLmpwMDw";
+
+    /// Deal the lines out like cards into `hands`, so that each group's rucksacks end up far apart.
+    fn shuffle(input: &str, hands: usize) -> String {
+        let lines = input.lines().collect::<Vec<_>>();
+        (0..hands)
+            .flat_map(|hand| lines.iter().skip(hand).step_by(hands))
+            .map(|line| format!("{line}\n"))
+            .collect()
+    }
+
+    #[test]
+    fn example() -> Result<(), Report> {
+        // Dealing into two hands puts lines 1, 3, 5 first, then lines 2, 4, 6.
+        // Besides the groups from the puzzle, there's another way to split them up.
+        let shuffled = shuffle(EXAMPLE, 2);
+        let partitions = discover_groups(&shuffled, &*LETTERS, &Layout::default(), 100)?;
+        let badges = partitions
+            .iter()
+            .map(|partition| {
+                let groups = partition.groups.iter();
+                groups
+                    .map(|group| (group.lines.clone(), group.badge))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            badges,
+            vec![
+                vec![(vec![1, 2, 4], 'r'), (vec![3, 5, 6], 'Z')],
+                vec![(vec![1, 3, 6], 'J'), (vec![2, 4, 5], 'q')],
+            ]
+        );
+        assert_eq!(partitions[0].score(), 70);
+
+        assert_eq!(
+            discover_groups(&shuffled, &*LETTERS, &Layout::default(), 1)?.len(),
+            1
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn input() -> Result<(), Report> {
+        let partitions = discover_groups(&shuffle(INPUT, 7), &*LETTERS, &Layout::default(), 1)?;
+        let mut lines = partitions[0]
+            .groups
+            .iter()
+            .flat_map(|group| group.lines.iter().copied())
+            .collect::<Vec<_>>();
+        lines.sort();
+        assert_eq!(lines, (1..=INPUT.lines().count()).collect::<Vec<_>>());
+        Ok(())
+    }
+
+    #[test]
+    fn impossible() -> Result<(), Report> {
+        let err =
+            discover_groups("aa\nbb\n", &*LETTERS, &Layout::default(), 1).expect_err("must error");
+        assert_eq!(
+            err.to_string(),
+            "2 rucksacks can't be split into groups of 3"
+        );
+
+        let err = discover_groups("aa\naa\nbb\n", &*LETTERS, &Layout::default(), 1)
+            .expect_err("must error");
+        assert_eq!(
+            err.to_string(),
+            "the rucksack on line 1 doesn't share exactly one item type with any group of 3"
+        );
+
+        let err =
+            discover_groups(EXAMPLE, &*LETTERS, &Layout::default(), 0).expect_err("must error");
+        assert_eq!(err.to_string(), "at least one partition must be asked for");
+
+        // Every rucksack shares one item type with the first, but nothing with each other.
+        let pairs = Layout::new(2, 2)?;
+        let err =
+            discover_groups("abxxcx\naa\nbb\ncc\n", &*LETTERS, &pairs, 1).expect_err("must error");
+        assert_eq!(
+            err.to_string(),
+            "no way to split the 4 rucksacks into groups of 2 that each share exactly one item type"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::BTreeMap;
+
+/// Every rucksack in an inventory, for answering questions about what the elves are carrying.
+///
+/// Rucksacks are identified by the line of the inventory they're on, starting from 1.
+/// Nothing here depends on how the rucksacks are split into compartments or groups,
+/// or on the priorities of their item types.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inventory {
+    rucksacks: Vec<String>,
+}
+
+impl Inventory {
+    /// Read an inventory with the items in each rucksack on their own line.
+    pub fn new(input: &str) -> Self {
+        Self {
+            rucksacks: input.lines().map(ToOwned::to_owned).collect(),
+        }
+    }
+
+    /// The number of rucksacks.
+    pub fn len(&self) -> usize {
+        self.rucksacks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rucksacks.is_empty()
+    }
+
+    /// The items in the rucksack on the given line.
+    pub fn rucksack(&self, line: usize) -> Option<&str> {
+        let index = line.checked_sub(1)?;
+        self.rucksacks.get(index).map(String::as_str)
+    }
+
+    /// Every rucksack, along with the line it's on.
+    pub fn rucksacks(&self) -> impl Iterator<Item = (usize, &str)> {
+        (1..).zip(self.rucksacks.iter().map(String::as_str))
+    }
+
+    /// The lines of the rucksacks that contain the item type.
+    pub fn containing(&self, item: char) -> Vec<usize> {
+        self.rucksacks()
+            .filter(|(_, contents)| contents.contains(item))
+            .map(|(line, _)| line)
+            .collect()
+    }
+
+    /// How many items of each type there are across every rucksack.
+    pub fn frequencies(&self) -> BTreeMap<char, usize> {
+        let mut frequencies = BTreeMap::new();
+        for item in self.rucksacks.iter().flat_map(|contents| contents.chars()) {
+            *frequencies.entry(item).or_default() += 1;
+        }
+        frequencies
+    }
+
+    /// The item types in every rucksack, in order. There are none if there are no rucksacks.
+    pub fn in_every_rucksack(&self) -> Vec<char> {
+        let Some(first) = self.rucksacks.first() else {
+            return Vec::new();
+        };
+        let mut items = first.chars().collect::<Vec<_>>();
+        items.sort_unstable();
+        items.dedup();
+        items.retain(|item| {
+            self.rucksacks
+                .iter()
+                .all(|contents| contents.contains(*item))
+        });
+        items
+    }
+
+    /// The line of the rucksack with the most items of the type, and how many it has.
+    /// If more than one rucksack has the most, the first one is chosen.
+    /// There's no such rucksack if no rucksack contains the item type.
+    pub fn most_of(&self, item: char) -> Option<(usize, usize)> {
+        self.rucksacks()
+            .map(|(line, contents)| (line, count(contents, item)))
+            .filter(|(_, count)| *count > 0)
+            .reduce(|most, rucksack| if rucksack.1 > most.1 { rucksack } else { most })
+    }
+}
+
+/// How many items of the type are in the rucksack.
+fn count(contents: &str, item: char) -> usize {
+    contents.chars().filter(|c| *c == item).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day3::INPUT;
+
+    const EXAMPLE: &str = "vJrwpWtwJgWrhcsFMMfFFhFp
+jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+PmmdzqPrVvPwwTWBwg
+wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
+ttgJtRGJQctTZtZT
+CrZsJsPPZsGzwwsLwLmpwMDw";
+
+    #[test]
+    fn queries() {
+        let inventory = Inventory::new(EXAMPLE);
+        assert_eq!(inventory.len(), 6);
+        assert_eq!(inventory.rucksack(3), Some("PmmdzqPrVvPwwTWBwg"));
+        assert_eq!(inventory.rucksack(0), None);
+        assert_eq!(inventory.rucksack(7), None);
+
+        assert_eq!(inventory.containing('r'), vec![1, 2, 3, 6]);
+        assert_eq!(inventory.containing('x'), Vec::<usize>::new());
+
+        let frequencies = inventory.frequencies();
+        assert_eq!(frequencies[&'F'], 8);
+        assert_eq!(frequencies.get(&'x'), None);
+
+        // Only 'r' is in each of the first three rucksacks, but not the fourth.
+        assert_eq!(inventory.in_every_rucksack(), Vec::<char>::new());
+        let first_three = Inventory::new(&EXAMPLE.lines().take(3).collect::<Vec<_>>().join("\n"));
+        assert_eq!(first_three.in_every_rucksack(), vec!['r']);
+
+        // The second, fifth and sixth rucksacks all have two 'Z's; the second comes first.
+        assert_eq!(inventory.most_of('Z'), Some((2, 2)));
+        assert_eq!(inventory.most_of('F'), Some((1, 4)));
+        assert_eq!(inventory.most_of('x'), None);
+    }
+
+    #[test]
+    fn input() {
+        let inventory = Inventory::new(INPUT);
+        assert_eq!(inventory.len(), INPUT.lines().count());
+        let total = inventory.frequencies().values().sum::<usize>();
+        assert_eq!(
+            total,
+            INPUT
+                .lines()
+                .map(|line| line.chars().count())
+                .sum::<usize>()
+        );
+    }
+}
@@ -0,0 +1,216 @@
+use std::{collections::HashMap, iter};
+
+/// Gives every item type in an inventory its own bit in an [`ItemSet`], in the order they're first seen.
+///
+/// Bits are handed out per item type rather than per priority, so item types that share a priority
+/// are still told apart, and any number of item types can be stored.
+#[derive(Debug, Clone)]
+pub struct ItemIndex {
+    /// ASCII item types are looked up directly, since they're by far the most common.
+    ascii: [Option<usize>; 128],
+    others: HashMap<char, usize>,
+    items: Vec<char>,
+}
+
+impl ItemIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The bit for the item type, handing out the next one if it hasn't been seen before.
+    pub fn index(&mut self, item: char) -> usize {
+        let next = self.items.len();
+        let index = match self.ascii.get_mut(item as usize) {
+            Some(index) => *index.get_or_insert(next),
+            None => *self.others.entry(item).or_insert(next),
+        };
+        if index == next {
+            self.items.push(item);
+        }
+        index
+    }
+
+    /// The item type with the given bit, if one has been handed out.
+    pub fn item(&self, index: usize) -> Option<char> {
+        self.items.get(index).copied()
+    }
+
+    /// The number of item types seen so far.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+impl Default for ItemIndex {
+    fn default() -> Self {
+        Self {
+            ascii: [None; 128],
+            others: HashMap::new(),
+            items: Vec::new(),
+        }
+    }
+}
+
+/// A set of item types, stored as bits indexed by an [`ItemIndex`].
+///
+/// The first 64 item types of an inventory are kept in a single `u64`, which covers the 52 letters
+/// with room to spare; any beyond that spill over into more words.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ItemSet {
+    low: u64,
+
+    /// The bits for item types 64 and up, without any trailing empty words,
+    /// so that equal sets are always stored the same way.
+    high: Vec<u64>,
+}
+
+impl ItemSet {
+    /// An empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The set of item types in the input, handing out bits for any new ones.
+    pub fn parse(input: &str, index: &mut ItemIndex) -> Self {
+        let mut items = Self::new();
+        for item in input.chars() {
+            items.insert(index.index(item));
+        }
+        items
+    }
+
+    /// Add the item type with the given bit to the set.
+    pub fn insert(&mut self, index: usize) {
+        let (word, bit) = (index / 64, index % 64);
+        if word == 0 {
+            self.low |= 1 << bit;
+            return;
+        }
+        if self.high.len() < word {
+            self.high.resize(word, 0);
+        }
+        self.high[word - 1] |= 1 << bit;
+    }
+
+    /// Whether the item type with the given bit is in the set.
+    pub fn contains(&self, index: usize) -> bool {
+        let word = match index / 64 {
+            0 => Some(self.low),
+            word => self.high.get(word - 1).copied(),
+        };
+        word.is_some_and(|word| word & (1 << (index % 64)) != 0)
+    }
+
+    /// The item types in both sets.
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut high = self
+            .high
+            .iter()
+            .zip(&other.high)
+            .map(|(a, b)| a & b)
+            .collect::<Vec<_>>();
+        while high.last() == Some(&0) {
+            high.pop();
+        }
+        Self {
+            low: self.low & other.low,
+            high,
+        }
+    }
+
+    /// The item types in either set.
+    pub fn union(&self, other: &Self) -> Self {
+        let (longer, shorter) = if self.high.len() >= other.high.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let mut high = longer.high.clone();
+        for (word, other) in high.iter_mut().zip(&shorter.high) {
+            *word |= other;
+        }
+        Self {
+            low: self.low | other.low,
+            high,
+        }
+    }
+
+    /// The number of item types in the set.
+    pub fn len(&self) -> usize {
+        iter::once(&self.low)
+            .chain(&self.high)
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.low == 0 && self.high.is_empty()
+    }
+
+    /// The bits of the item types in the set, from lowest to highest.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        iter::once(&self.low)
+            .chain(&self.high)
+            .enumerate()
+            .flat_map(|(word, bits)| {
+                let mut bits = *bits;
+                iter::from_fn(move || {
+                    if bits == 0 {
+                        return None;
+                    }
+                    let bit = bits.trailing_zeros() as usize;
+                    bits &= bits - 1;
+                    Some(word * 64 + bit)
+                })
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operations() {
+        let mut index = ItemIndex::new();
+        let first = ItemSet::parse("vJrwpWtwJgWr", &mut index);
+        let second = ItemSet::parse("hcsFMMfFFhFp", &mut index);
+        assert_eq!(first.len(), 8);
+        assert_eq!(index.len(), 14);
+        assert_eq!(index.item(4), Some('p'));
+        assert!(first.contains(4));
+        assert!(!first.contains(200));
+
+        let shared = first.intersection(&second);
+        assert_eq!(shared.iter().collect::<Vec<_>>(), vec![4]);
+        assert_eq!(first.union(&second).len(), first.len() + second.len() - 1);
+        assert!(ItemSet::new().is_empty());
+
+        let mut items = ItemSet::new();
+        items.insert(200);
+        items.insert(63);
+        items.insert(0);
+        assert_eq!(items.iter().collect::<Vec<_>>(), vec![0, 63, 200]);
+        assert!(items.contains(200));
+
+        // Sets that only shared item types beyond the first word are empty, and equal to any other empty set.
+        let mut other = ItemSet::new();
+        other.insert(100);
+        assert_eq!(items.intersection(&other), ItemSet::new());
+        assert_eq!(items.union(&other).len(), 4);
+    }
+
+    #[test]
+    fn many_item_types() {
+        // Far more item types than fit in a single word, all told apart.
+        let mut index = ItemIndex::new();
+        let items = ('一'..='龥').take(200).collect::<String>();
+        let set = ItemSet::parse(&items, &mut index);
+        assert_eq!(set.len(), 200);
+        assert_eq!(index.item(199), items.chars().last());
+    }
+}
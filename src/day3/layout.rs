@@ -0,0 +1,49 @@
+use stable_eyre::{eyre::ensure, Report};
+
+/// How the elves pack their rucksacks and form groups: how many compartments each rucksack has,
+/// and how many rucksacks are in each group.
+///
+/// The puzzle uses two compartments and groups of three, which is the [`Default`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Layout {
+    compartments: usize,
+    group_size: usize,
+}
+
+impl Layout {
+    /// Rucksacks need at least two compartments for an item type to be shared between them,
+    /// and groups need at least one rucksack.
+    pub fn new(compartments: usize, group_size: usize) -> Result<Self, Report> {
+        ensure!(
+            compartments >= 2,
+            "rucksacks must have at least two compartments, got {compartments}"
+        );
+        ensure!(
+            group_size >= 1,
+            "groups must have at least one rucksack, got {group_size}"
+        );
+        Ok(Self {
+            compartments,
+            group_size,
+        })
+    }
+
+    /// The number of compartments in each rucksack.
+    pub fn compartments(&self) -> usize {
+        self.compartments
+    }
+
+    /// The number of rucksacks in each group.
+    pub fn group_size(&self) -> usize {
+        self.group_size
+    }
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self {
+            compartments: 2,
+            group_size: 3,
+        }
+    }
+}
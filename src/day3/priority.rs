@@ -0,0 +1,125 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use once_cell::sync::Lazy;
+use stable_eyre::{
+    eyre::{bail, Context},
+    Report,
+};
+
+/// Converts item types to priorities.
+///
+/// Any function from an item type to an optional priority is a scheme,
+/// so item types can be weighted however is needed:
+/// ```
+/// # use aoc22::day3::{part1_with, PriorityScheme};
+/// let greek = |item: char| ('α'..='ω').position(|letter| letter == item).map(|index| index + 1);
+/// assert_eq!(part1_with("αβγα", &greek)?, 1);
+/// # Ok::<(), stable_eyre::Report>(())
+/// ```
+pub trait PriorityScheme {
+    /// The priority of the item type, or `None` if the item type doesn't have one.
+    fn priority(&self, item: char) -> Option<usize>;
+}
+
+impl<F> PriorityScheme for F
+where
+    F: Fn(char) -> Option<usize>,
+{
+    fn priority(&self, item: char) -> Option<usize> {
+        self(item)
+    }
+}
+
+/// An ordered list of item types, where each item type's priority is its position in the list, starting from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alphabet {
+    /// ASCII item types are looked up directly, since they're by far the most common.
+    ascii: [Option<usize>; 128],
+    priorities: HashMap<char, usize>,
+}
+
+impl Alphabet {
+    /// The item types, in order of priority. Each item type may only be listed once.
+    pub fn new(items: impl IntoIterator<Item = char>) -> Result<Self, Report> {
+        let mut ascii = [None; 128];
+        let mut priorities = HashMap::new();
+        for (index, item) in items.into_iter().enumerate() {
+            if let Some(existing) = priorities.insert(item, index + 1) {
+                bail!(
+                    "item type {item:?} is listed more than once, first with priority {existing}"
+                );
+            }
+            if item.is_ascii() {
+                ascii[item as usize] = Some(index + 1);
+            }
+        }
+        Ok(Self { ascii, priorities })
+    }
+
+    /// Parse an alphabet listing the item types in order of priority.
+    /// Whitespace is ignored, so the item types can be laid out however is convenient, e.g. one per line.
+    pub fn parse(input: &str) -> Result<Self, Report> {
+        Self::new(input.chars().filter(|item| !item.is_whitespace()))
+    }
+
+    /// Load an alphabet from a file, as in [`Alphabet::parse`].
+    pub fn load(path: &Path) -> Result<Self, Report> {
+        let input =
+            fs::read_to_string(path).wrap_err_with(|| format!("read alphabet from {path:?}"))?;
+        Self::parse(&input).wrap_err_with(|| format!("load alphabet from {path:?}"))
+    }
+}
+
+impl Default for Alphabet {
+    /// The item types the elves usually use: [`LETTERS`].
+    fn default() -> Self {
+        LETTERS.clone()
+    }
+}
+
+impl PriorityScheme for Alphabet {
+    fn priority(&self, item: char) -> Option<usize> {
+        match self.ascii.get(item as usize) {
+            Some(priority) => *priority,
+            None => self.priorities.get(&item).copied(),
+        }
+    }
+}
+
+/// Every item type can be converted to a priority:
+///
+/// - Lowercase item types a through z have priorities 1 through 26.
+/// - Uppercase item types A through Z have priorities 27 through 52.
+pub static LETTERS: Lazy<Alphabet> =
+    Lazy::new(|| Alphabet::new(('a'..='z').chain('A'..='Z')).expect("letters must be unique"));
+
+/// Item types 0 through 9 have priorities 1 through 10.
+pub static DIGITS: Lazy<Alphabet> =
+    Lazy::new(|| Alphabet::new('0'..='9').expect("digits must be unique"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn letters() {
+        assert_eq!(LETTERS.priority('a'), Some(1));
+        assert_eq!(LETTERS.priority('b'), Some(2));
+        assert_eq!(LETTERS.priority('z'), Some(26));
+        assert_eq!(LETTERS.priority('A'), Some(27));
+        assert_eq!(LETTERS.priority('Z'), Some(52));
+        assert_eq!(LETTERS.priority('0'), None);
+        assert_eq!(DIGITS.priority('0'), Some(1));
+        assert_eq!(DIGITS.priority('9'), Some(10));
+    }
+
+    #[test]
+    fn alphabet() -> Result<(), Report> {
+        let alphabet = Alphabet::parse("α β γ\n🍎🍌\n")?;
+        assert_eq!(alphabet.priority('α'), Some(1));
+        assert_eq!(alphabet.priority('🍌'), Some(5));
+        assert_eq!(alphabet.priority(' '), None);
+        assert!(Alphabet::parse("abca").is_err());
+        Ok(())
+    }
+}
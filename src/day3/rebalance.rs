@@ -0,0 +1,276 @@
+use std::fmt;
+
+use stable_eyre::{
+    eyre::{bail, ensure, Context},
+    Report,
+};
+
+use super::{calculate_priority, PriorityScheme};
+
+/// One of the two compartments of a rucksack.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Side {
+    First,
+    Second,
+}
+
+impl Side {
+    /// The other compartment.
+    pub fn other(self) -> Self {
+        match self {
+            Side::First => Side::Second,
+            Side::Second => Side::First,
+        }
+    }
+}
+
+impl fmt::Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Side::First => write!(f, "first"),
+            Side::Second => write!(f, "second"),
+        }
+    }
+}
+
+/// Moving every item of one type out of a compartment and into the other.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Move {
+    pub item: char,
+
+    /// The number of items moved.
+    pub count: usize,
+
+    /// The compartment the items are moved out of.
+    pub from: Side,
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:?} {} -> {}",
+            self.count,
+            self.item,
+            self.from,
+            self.from.other()
+        )
+    }
+}
+
+/// The moves that leave a rucksack with no item type in both compartments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RucksackPlan {
+    /// The line of the inventory the rucksack is on.
+    pub line: usize,
+
+    pub moves: Vec<Move>,
+
+    /// The sum of the priorities of every item moved.
+    pub cost: usize,
+}
+
+impl RucksackPlan {
+    /// The number of items moved.
+    pub fn moved(&self) -> usize {
+        self.moves.iter().map(|m| m.count).sum()
+    }
+}
+
+/// The moves that fix every rucksack in an inventory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RebalancePlan {
+    pub rucksacks: Vec<RucksackPlan>,
+}
+
+impl RebalancePlan {
+    /// The number of items moved across every rucksack.
+    pub fn moved(&self) -> usize {
+        self.rucksacks.iter().map(RucksackPlan::moved).sum()
+    }
+
+    /// The sum of the priorities of every item moved across every rucksack.
+    pub fn cost(&self) -> usize {
+        self.rucksacks.iter().map(|rucksack| rucksack.cost).sum()
+    }
+}
+
+/// Work out the fewest item moves that leave each rucksack with no item type in both compartments.
+///
+/// Both compartments of a rucksack must still hold the same number of items afterwards,
+/// so every item type ends up wholly in one compartment or the other.
+/// When there's more than one way to move the fewest items, the one with the lowest cost is chosen,
+/// where moving an item costs its priority.
+pub fn rebalance(input: &str, scheme: &dyn PriorityScheme) -> Result<RebalancePlan, Report> {
+    let rucksacks = input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| {
+            let number = index + 1;
+            plan_rucksack(number, line, scheme)
+                .wrap_err_with(|| format!("rebalance rucksack on line {number}"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(RebalancePlan { rucksacks })
+}
+
+/// How many items of a type are in each compartment of a rucksack.
+struct ItemCount {
+    item: char,
+    priority: usize,
+    first: usize,
+    second: usize,
+}
+
+impl ItemCount {
+    fn total(&self) -> usize {
+        self.first + self.second
+    }
+}
+
+/// The fewest items moved, and then the lowest cost, for some choice of compartments.
+type Cost = (usize, usize);
+
+/// Each item type is either kept in the first compartment or kept in the second one,
+/// and the first compartment must end up with exactly half the items.
+/// That makes this a knapsack problem: pick the item types for the first compartment
+/// whose sizes add up to half the items, for the lowest cost.
+fn plan_rucksack(
+    line: usize,
+    input: &str,
+    scheme: &dyn PriorityScheme,
+) -> Result<RucksackPlan, Report> {
+    let items = input.chars().collect::<Vec<_>>();
+    ensure!(
+        items.len() % 2 == 0,
+        "{} items can't be split evenly between two compartments",
+        items.len()
+    );
+    let half = items.len() / 2;
+
+    // Item types are kept in the order they first appear, so that plans are predictable.
+    let mut counts: Vec<ItemCount> = Vec::new();
+    for (index, item) in items.iter().enumerate() {
+        let position = match counts.iter().position(|count| count.item == *item) {
+            Some(position) => position,
+            None => {
+                counts.push(ItemCount {
+                    item: *item,
+                    priority: calculate_priority(scheme, item)?,
+                    first: 0,
+                    second: 0,
+                });
+                counts.len() - 1
+            }
+        };
+        if index < half {
+            counts[position].first += 1;
+        } else {
+            counts[position].second += 1;
+        }
+    }
+
+    // `best[size]` is the lowest cost of the item types so far with `size` items kept in the first compartment,
+    // and `keep_first[type][size]` records whether that item type was kept in the first compartment to get there.
+    let mut best: Vec<Option<Cost>> = vec![None; half + 1];
+    best[0] = Some((0, 0));
+    let mut keep_first = Vec::with_capacity(counts.len());
+    for count in &counts {
+        let move_items =
+            |(moved, cost): Cost, moving: usize| (moved + moving, cost + moving * count.priority);
+
+        let mut next = vec![None; half + 1];
+        let mut kept_first = vec![false; half + 1];
+        for size in 0..=half {
+            let second = best[size].map(|cost| move_items(cost, count.first));
+            let first = size
+                .checked_sub(count.total())
+                .and_then(|previous| best[previous])
+                .map(|cost| move_items(cost, count.second));
+            (next[size], kept_first[size]) = match (first, second) {
+                (Some(first), Some(second)) if first < second => (Some(first), true),
+                (Some(first), None) => (Some(first), true),
+                (_, second) => (second, false),
+            };
+        }
+        best = next;
+        keep_first.push(kept_first);
+    }
+
+    let Some((_, cost)) = best[half] else {
+        bail!("no item types add up to {half} items for each compartment")
+    };
+
+    // Walk back through the choices made for each item type to find the moves.
+    let mut size = half;
+    let mut moves = Vec::new();
+    for (count, kept_first) in counts.iter().zip(&keep_first).rev() {
+        let (moving, from) = if kept_first[size] {
+            size -= count.total();
+            (count.second, Side::Second)
+        } else {
+            (count.first, Side::First)
+        };
+        if moving > 0 {
+            moves.push(Move {
+                item: count.item,
+                count: moving,
+                from,
+            });
+        }
+    }
+    moves.reverse();
+
+    Ok(RucksackPlan { line, moves, cost })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day3::{INPUT, LETTERS};
+
+    #[test]
+    fn example() -> Result<(), Report> {
+        // Moving p either way leaves the compartments a single item apart, so one more item has to move.
+        // Moving p (16) to the second compartment and c (3) to the first costs less than any other pair.
+        let plan = rebalance("vJrwpWtwJgWrhcsFMMfFFhFp", &*LETTERS)?;
+        assert_eq!(
+            plan.rucksacks[0].moves,
+            vec![
+                Move {
+                    item: 'p',
+                    count: 1,
+                    from: Side::First
+                },
+                Move {
+                    item: 'c',
+                    count: 1,
+                    from: Side::Second
+                },
+            ]
+        );
+        assert_eq!((plan.moved(), plan.cost()), (2, 19));
+
+        // A rucksack that's already fine needs no moves.
+        let plan = rebalance("abcd", &*LETTERS)?;
+        assert_eq!(plan.moved(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn input() -> Result<(), Report> {
+        let plan = rebalance(INPUT, &*LETTERS)?;
+        assert_eq!(plan.rucksacks.len(), INPUT.lines().count());
+
+        // Every rucksack shares an item type, so at least one item has to move.
+        assert!(plan.rucksacks.iter().all(|rucksack| rucksack.moved() > 0));
+        Ok(())
+    }
+
+    #[test]
+    fn impossible() {
+        // Three of the four items are the same type, so they can't all fit in one compartment.
+        assert!(rebalance("aaab", &*LETTERS).is_err());
+        assert!(rebalance("abc", &*LETTERS).is_err());
+    }
+}
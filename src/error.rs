@@ -0,0 +1,125 @@
+use std::{error::Error, fmt};
+
+/// An error encountered while parsing a single line of puzzle input.
+///
+/// Renders as the message followed by a snippet of the offending line,
+/// with carets pointing at the text that could not be parsed:
+///
+/// ```not_rust
+/// expected player move 'X', 'Y' or 'Z' at line 3, column 3
+///   |
+/// 3 | C Q
+///   |   ^
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    message: String,
+    line: usize,
+    column: usize,
+    text: String,
+    source_line: String,
+}
+
+impl ParseError {
+    /// An error covering the entirety of `source_line`, which is line `line` of the input (starting at 1).
+    pub fn line(message: impl Into<String>, line: usize, source_line: &str) -> Self {
+        Self::span(message, line, source_line, source_line)
+    }
+
+    /// An error covering `text`, which is a slice of `source_line`,
+    /// which is in turn line `line` of the input (starting at 1).
+    ///
+    /// If `text` is not actually a slice of `source_line`, the error points at the start of the line.
+    pub fn span(message: impl Into<String>, line: usize, source_line: &str, text: &str) -> Self {
+        let start = source_line.as_ptr() as usize;
+        let offset = (text.as_ptr() as usize)
+            .checked_sub(start)
+            .filter(|offset| offset + text.len() <= source_line.len())
+            .filter(|offset| source_line.is_char_boundary(*offset))
+            .unwrap_or_default();
+
+        Self {
+            message: message.into(),
+            line,
+            column: source_line[..offset].chars().count() + 1,
+            text: text.to_owned(),
+            source_line: source_line.to_owned(),
+        }
+    }
+
+    /// What went wrong.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The line of the input the error occurred on, starting at 1.
+    pub fn line_number(&self) -> usize {
+        self.line
+    }
+
+    /// The column of the line at which the offending text starts, starting at 1.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// The text that could not be parsed.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            message,
+            line,
+            column,
+            text,
+            source_line,
+        } = self;
+
+        let gutter = " ".repeat(line.to_string().len());
+        let padding = " ".repeat(column - 1);
+        let carets = "^".repeat(text.chars().count().max(1));
+
+        writeln!(f, "{message} at line {line}, column {column}")?;
+        writeln!(f, "{gutter} |")?;
+        writeln!(f, "{line} | {source_line}")?;
+        write!(f, "{gutter} | {padding}{carets}")
+    }
+}
+
+impl Error for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn span_column() {
+        let line = "A  Q";
+        let err = ParseError::span("bad move", 12, line, &line[3..]);
+        assert_eq!(err.line_number(), 12);
+        assert_eq!(err.column(), 4);
+        assert_eq!(err.text(), "Q");
+    }
+
+    #[test]
+    fn span_outside_line() {
+        let err = ParseError::span("bad move", 1, "A X", "Q");
+        assert_eq!(err.column(), 1);
+        assert_eq!(err.text(), "Q");
+    }
+
+    #[test]
+    fn render_snippet() {
+        let line = "C Q";
+        let err = ParseError::span("expected player move", 3, line, &line[2..]);
+        let expected = "expected player move at line 3, column 3\n  |\n3 | C Q\n  |   ^";
+        assert_eq!(err.to_string(), expected);
+
+        let err = ParseError::line("invalid calories", 1024, "12a");
+        let expected = "invalid calories at line 1024, column 1\n     |\n1024 | 12a\n     | ^^^";
+        assert_eq!(err.to_string(), expected);
+    }
+}
@@ -0,0 +1,10 @@
+pub mod answer;
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod error;
+pub mod solution;
+
+pub use answer::Answer;
+pub use error::ParseError;
+pub use solution::{Solution, SOLUTIONS};
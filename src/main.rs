@@ -1,25 +1,700 @@
-use stable_eyre::Report;
+use std::{
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Read},
+    num::NonZeroUsize,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
-mod day1;
-mod day2;
-mod day3;
+use aoc22::{day2, day3, solution, Answer, Solution, SOLUTIONS};
+use clap::{Args, Parser, Subcommand};
+use stable_eyre::{
+    eyre::{bail, ensure, Context},
+    Report,
+};
 
-macro_rules! run_day {
-    ($day:ident) => {{
-        println!("== {} ==", stringify!($day));
-        println!(" part1: {}", $day::part1($day::INPUT)?);
-        println!(" part2: {}", $day::part2($day::INPUT)?);
-        println!();
-    }};
+/// Solutions for advent of code 2022.
+#[derive(Parser)]
+#[command(version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the solutions for one or more days.
+    Run {
+        /// The day (`2`) or inclusive range of days (`1-3`) to run.
+        /// Every implemented day is run if this is omitted.
+        days: Option<Days>,
+
+        /// Only run the given part of each day.
+        #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+        part: Option<u8>,
+
+        /// Read the puzzle input from this file instead of the built-in input.
+        /// Use `-` to read from stdin. Only valid when running a single day.
+        #[arg(short, long)]
+        input: Option<PathBuf>,
+
+        /// Check the answer against this expected answer, failing if they differ.
+        /// Answers drawn across several lines are given with a newline between each row.
+        /// Only valid when running a single part of a single day.
+        #[arg(short, long, requires = "part", value_parser = Answer::from_str)]
+        expect: Option<Answer>,
+    },
+
+    /// Explore the day 2 strategy guide.
+    Day2 {
+        #[command(subcommand)]
+        command: Day2Command,
+    },
+
+    /// Explore the day 3 rucksack inventory.
+    Day3 {
+        #[command(subcommand)]
+        command: Day3Command,
+    },
+}
+
+#[derive(Subcommand)]
+enum Day2Command {
+    /// Score a strategy guide using a different cipher.
+    ///
+    /// The guide is scored as it's read, so guides of any size can be scored.
+    Score {
+        #[command(flatten)]
+        guide: GuideArgs,
+
+        /// Only score the given part. Required when reading the guide from stdin,
+        /// since it can only be read once.
+        #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+        part: Option<u8>,
+
+        /// Score the guide across this many threads.
+        #[arg(short, long)]
+        threads: Option<NonZeroUsize>,
+    },
+
+    /// Try every reading of the second column of a strategy guide, and rank them.
+    Crack {
+        #[command(flatten)]
+        guide: GuideArgs,
+
+        /// How to rank the readings: `highest`, `narrowest-win`, or `closest=N` for the score closest to `N`.
+        #[arg(short, long, default_value = "narrowest-win", value_parser = day2::Ranking::from_str)]
+        rank: day2::Ranking,
+    },
+
+    /// Show how every round of a strategy guide played out, along with statistics about the match.
+    Report {
+        #[command(flatten)]
+        guide: GuideArgs,
+
+        /// Read the second column as moves (part 1) or as outcomes (part 2).
+        #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+        part: u8,
+
+        /// Print the report as a `table`, `csv` or `json`.
+        #[arg(short, long, default_value = "table", value_parser = day2::ReportFormat::from_str)]
+        format: day2::ReportFormat,
+    },
+
+    /// Compare the strategy guide against players that ignore it and predict the opponent's moves instead.
+    Predict {
+        #[command(flatten)]
+        guide: GuideArgs,
+    },
+
+    /// Run a tournament between the strategy guide and a field of other strategies.
+    Tournament {
+        #[command(flatten)]
+        guide: GuideArgs,
+
+        /// Play a Swiss tournament over this many rounds, instead of a round robin.
+        #[arg(long)]
+        swiss: Option<usize>,
+
+        /// The number of rounds of the game in each match.
+        #[arg(short, long, default_value_t = 100)]
+        rounds: usize,
+
+        /// The seed for the random number generator, so that a tournament can be replayed.
+        #[arg(short, long, default_value_t = 2022)]
+        seed: u64,
+    },
+}
+
+#[derive(Subcommand)]
+enum Day3Command {
+    /// Sum the priorities of an inventory using a different alphabet of item types or layout.
+    Score {
+        #[command(flatten)]
+        inventory: InventoryArgs,
+
+        #[command(flatten)]
+        layout: LayoutArgs,
+
+        /// Only score the given part.
+        #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+        part: Option<u8>,
+    },
+
+    /// Check every rucksack and group, listing the ones that don't share exactly one item type
+    /// and scoring the rest.
+    Diagnose {
+        #[command(flatten)]
+        inventory: InventoryArgs,
+
+        #[command(flatten)]
+        layout: LayoutArgs,
+    },
+
+    /// Answer questions about what's in the rucksacks.
+    Query {
+        /// Read the inventory from this file instead of the built-in input.
+        /// Use `-` to read from stdin.
+        #[arg(short, long)]
+        input: Option<PathBuf>,
+
+        #[command(subcommand)]
+        query: QueryCommand,
+    },
+
+    /// Plan the fewest item moves that leave no item type in both compartments of any rucksack.
+    Rebalance {
+        #[command(flatten)]
+        inventory: InventoryArgs,
+    },
+
+    /// Split rucksacks listed in any order into groups that each share exactly one badge.
+    Groups {
+        #[command(flatten)]
+        inventory: InventoryArgs,
+
+        #[command(flatten)]
+        layout: LayoutArgs,
+
+        /// Find at most this many ways of splitting the rucksacks.
+        #[arg(short, long, default_value_t = 1)]
+        limit: usize,
+    },
+}
+
+#[derive(Subcommand)]
+enum QueryCommand {
+    /// List the rucksacks that contain the item type.
+    Contains { item: char },
+
+    /// Count the items of each type across every rucksack.
+    Frequencies,
+
+    /// List the item types that are in every rucksack.
+    Common,
+
+    /// Find the rucksack with the most items of the type.
+    Most { item: char },
+}
+
+/// The rucksack inventory to analyse, and how to prioritize its item types.
+#[derive(Args)]
+struct InventoryArgs {
+    /// Prioritize item types by their order in this file, starting from 1. Whitespace in the file is ignored.
+    /// The letters a-z then A-Z are used if this is omitted.
+    #[arg(short, long)]
+    alphabet: Option<PathBuf>,
+
+    /// Read the inventory from this file instead of the built-in input.
+    /// Use `-` to read from stdin.
+    #[arg(short, long)]
+    input: Option<PathBuf>,
+}
+
+impl InventoryArgs {
+    /// Load the alphabet of item types, and the inventory.
+    fn load(&self) -> Result<(day3::Alphabet, String), Report> {
+        let alphabet = match &self.alphabet {
+            Some(path) => day3::Alphabet::load(path)?,
+            None => day3::Alphabet::default(),
+        };
+        let input = match &self.input {
+            Some(path) => read_input(path)?,
+            None => day3::INPUT.to_owned(),
+        };
+        Ok((alphabet, input))
+    }
+}
+
+/// How rucksacks are packed and grouped.
+#[derive(Args)]
+struct LayoutArgs {
+    /// The number of compartments in each rucksack.
+    #[arg(long, default_value_t = 2)]
+    compartments: usize,
+
+    /// The number of rucksacks in each group.
+    #[arg(long, default_value_t = 3)]
+    group_size: usize,
+}
+
+impl LayoutArgs {
+    fn layout(&self) -> Result<day3::Layout, Report> {
+        day3::Layout::new(self.compartments, self.group_size)
+    }
+}
+
+/// The strategy guide to analyse, and how to read it.
+#[derive(Args)]
+struct GuideArgs {
+    /// Read the guide with the cipher in this `.toml` or `.json` file.
+    /// The classic Rock Paper Scissors cipher is used if this is omitted.
+    #[arg(short, long)]
+    cipher: Option<PathBuf>,
+
+    /// Read the strategy guide from this file instead of the built-in input.
+    /// Use `-` to read from stdin.
+    #[arg(short, long)]
+    input: Option<PathBuf>,
+}
+
+impl GuideArgs {
+    /// Load the rules described by the cipher, and the strategy guide.
+    fn load(&self) -> Result<(day2::GameRules, String), Report> {
+        let input = match &self.input {
+            Some(path) => read_input(path)?,
+            None => day2::INPUT.to_owned(),
+        };
+        Ok((self.rules()?, input))
+    }
+
+    /// Load the rules described by the cipher.
+    fn rules(&self) -> Result<day2::GameRules, Report> {
+        let cipher = match &self.cipher {
+            Some(path) => day2::Cipher::load(path)?,
+            None => day2::Cipher::default(),
+        };
+        cipher.rules().wrap_err("invalid cipher")
+    }
+
+    /// Open the strategy guide to be read one line at a time.
+    fn open(&self) -> Result<Box<dyn BufRead>, Report> {
+        match &self.input {
+            Some(path) if path == Path::new("-") => Ok(Box::new(io::stdin().lock())),
+            Some(path) => {
+                let file = File::open(path).wrap_err_with(|| format!("open input {path:?}"))?;
+                Ok(Box::new(BufReader::new(file)))
+            }
+            None => Ok(Box::new(day2::INPUT.as_bytes())),
+        }
+    }
+
+    /// Whether the strategy guide is read from stdin.
+    fn is_stdin(&self) -> bool {
+        self.input.as_deref() == Some(Path::new("-"))
+    }
 }
 
 fn main() -> Result<(), Report> {
+    let cli = Cli::parse();
+
+    match cli.command {
+        None => run(Days::all(), None, None, None),
+        Some(Command::Run {
+            days,
+            part,
+            input,
+            expect,
+        }) => {
+            let days = days.unwrap_or_else(Days::all);
+            ensure!(
+                expect.is_none() || days.is_single(),
+                "an expected answer can only be provided when running a single day"
+            );
+            let input = match input {
+                Some(path) => {
+                    ensure!(
+                        days.is_single(),
+                        "an input can only be provided when running a single day"
+                    );
+                    Some(read_input(&path)?)
+                }
+                None => None,
+            };
+            run(days, part, input.as_deref(), expect.as_ref())
+        }
+        Some(Command::Day2 { command }) => run_day2(command),
+        Some(Command::Day3 { command }) => run_day3(command),
+    }
+}
+
+fn run(
+    days: Days,
+    part: Option<u8>,
+    input: Option<&str>,
+    expect: Option<&Answer>,
+) -> Result<(), Report> {
     println!("advent of code 2022");
     println!();
 
-    run_day!(day1);
-    run_day!(day2);
-    run_day!(day3);
+    for day in days.0 {
+        let Some(solution) = solution::find(day) else { bail!("day {day} is not implemented") };
+        run_solution(solution, part, input, expect)?;
+    }
+
+    Ok(())
+}
+
+fn run_solution(
+    solution: &dyn Solution,
+    part: Option<u8>,
+    input: Option<&str>,
+    expect: Option<&Answer>,
+) -> Result<(), Report> {
+    let input = input.unwrap_or_else(|| solution.input());
+    println!("== day{}: {} ==", solution.day(), solution.title());
+    if matches!(part, None | Some(1)) {
+        report_answer("part1", solution.part1(input)?, expect)?;
+    }
+    if matches!(part, None | Some(2)) {
+        report_answer("part2", solution.part2(input)?, expect)?;
+    }
+    println!();
+    Ok(())
+}
+
+fn report_answer(part: &str, answer: Answer, expect: Option<&Answer>) -> Result<(), Report> {
+    match &answer {
+        Answer::Grid(rows) => {
+            println!(" {part}:");
+            for row in rows {
+                println!("  {row}");
+            }
+        }
+        _ => println!(" {part}: {answer}"),
+    }
+
+    if let Some(expected) = expect {
+        ensure!(
+            &answer == expected,
+            "{part} answer '{answer}' does not match expected answer '{expected}'"
+        );
+        println!(" {part}: matches expected answer");
+    }
 
     Ok(())
 }
+
+fn run_day2(command: Day2Command) -> Result<(), Report> {
+    match command {
+        Day2Command::Score {
+            guide,
+            part,
+            threads,
+        } => {
+            ensure!(
+                part.is_some() || !guide.is_stdin(),
+                "a part must be provided when reading the guide from stdin"
+            );
+
+            let rules = guide.rules()?;
+            println!("== day2: scored with cipher ==");
+            if matches!(part, None | Some(1)) {
+                let score = match threads {
+                    Some(threads) => day2::part1_parallel(guide.open()?, &rules, threads)?,
+                    None => day2::part1_reader(guide.open()?, &rules)?,
+                };
+                report_answer("part1", score.into(), None)?;
+            }
+            if matches!(part, None | Some(2)) {
+                let score = match threads {
+                    Some(threads) => day2::part2_parallel(guide.open()?, &rules, threads)?,
+                    None => day2::part2_reader(guide.open()?, &rules)?,
+                };
+                report_answer("part2", score.into(), None)?;
+            }
+        }
+        Day2Command::Crack { guide, rank } => {
+            let (rules, input) = guide.load()?;
+            println!("== day2: readings of the second column ==");
+            for (rank, interpretation) in day2::crack(&input, &rules, rank)?.iter().enumerate() {
+                println!(
+                    " {:>3}. {}: {} to {} ({:+}), {} wins, {} draws, {} losses",
+                    rank + 1,
+                    interpretation.reading.describe(&rules),
+                    interpretation.score,
+                    interpretation.opponent_score,
+                    interpretation.margin(),
+                    interpretation.wins,
+                    interpretation.draws,
+                    interpretation.losses,
+                );
+            }
+        }
+        Day2Command::Report {
+            guide,
+            part,
+            format,
+        } => {
+            // The report is printed on its own, so that it can be piped into other tools.
+            let (rules, input) = guide.load()?;
+            let report = match part {
+                1 => day2::report_part1(&input, &rules)?,
+                _ => day2::report_part2(&input, &rules)?,
+            };
+            print!("{}", report.render(&rules, format)?);
+            return Ok(());
+        }
+        Day2Command::Predict { guide } => {
+            let (rules, input) = guide.load()?;
+            println!("== day2: guide against predictors ==");
+            for (name, score) in day2::compare(&input, &rules)? {
+                println!(" {name}: {score}");
+            }
+        }
+        Day2Command::Tournament {
+            guide,
+            swiss,
+            rounds,
+            seed,
+        } => {
+            let (rules, input) = guide.load()?;
+            let mut entrants = vec![
+                day2::Entrant::guide_moves("guide as moves", &input, &rules)?,
+                day2::Entrant::guide_outcomes("guide as outcomes", &input, &rules)?,
+                day2::Entrant::random("random"),
+                day2::Entrant::adaptive("frequency", day2::FrequencyPredictor::new),
+                day2::Entrant::adaptive("markov(1)", || day2::MarkovPredictor::new(1)),
+                day2::Entrant::adaptive("markov(2)", || day2::MarkovPredictor::new(2)),
+                day2::Entrant::adaptive("pattern(8)", || day2::PatternPredictor::new(8)),
+            ];
+            for shape in rules.shapes() {
+                let name = format!("always {}", rules.rule(shape).name.to_lowercase());
+                entrants.push(day2::Entrant::fixed(name, vec![shape])?);
+            }
+
+            let tournament = day2::Tournament {
+                pairing: swiss.map_or(day2::Pairing::RoundRobin, day2::Pairing::Swiss),
+                rounds_per_match: rounds,
+                seed,
+            };
+            let result = tournament.run(&rules, &entrants)?;
+            match swiss {
+                Some(swiss) => println!("== day2: swiss tournament over {swiss} rounds =="),
+                None => println!("== day2: round robin tournament =="),
+            }
+            print!("{}", result.table());
+        }
+    }
+
+    println!();
+    Ok(())
+}
+
+fn run_day3(command: Day3Command) -> Result<(), Report> {
+    match command {
+        Day3Command::Score {
+            inventory,
+            layout,
+            part,
+        } => {
+            let (alphabet, input) = inventory.load()?;
+            let layout = layout.layout()?;
+            println!("== day3: scored with alphabet ==");
+            if matches!(part, None | Some(1)) {
+                let score = day3::part1_with_layout(&input, &alphabet, &layout)?;
+                report_answer("part1", score.into(), None)?;
+            }
+            if matches!(part, None | Some(2)) {
+                let score = day3::part2_with_layout(&input, &alphabet, &layout)?;
+                report_answer("part2", score.into(), None)?;
+            }
+        }
+        Day3Command::Diagnose { inventory, layout } => {
+            let (alphabet, input) = inventory.load()?;
+            let diagnosis = day3::diagnose(&input, &alphabet, &layout.layout()?);
+            println!("== day3: diagnosis ==");
+            for problem in diagnosis.problems() {
+                println!(" {problem}");
+            }
+            println!(" problems: {}", diagnosis.problems().count());
+            report_answer("part1", diagnosis.part1().into(), None)?;
+            report_answer("part2", diagnosis.part2().into(), None)?;
+        }
+        Day3Command::Query { input, query } => {
+            let input = match &input {
+                Some(path) => read_input(path)?,
+                None => day3::INPUT.to_owned(),
+            };
+            let inventory = day3::Inventory::new(&input);
+            println!("== day3: inventory of {} rucksacks ==", inventory.len());
+            match query {
+                QueryCommand::Contains { item } => {
+                    let lines = inventory.containing(item);
+                    let list = lines.iter().map(ToString::to_string).collect::<Vec<_>>();
+                    println!(
+                        " {item:?} is in {} rucksacks: {}",
+                        lines.len(),
+                        list.join(", ")
+                    );
+                }
+                QueryCommand::Frequencies => {
+                    for (item, count) in inventory.frequencies() {
+                        println!(" {item:?}: {count}");
+                    }
+                }
+                QueryCommand::Common => {
+                    let items = inventory.in_every_rucksack();
+                    if items.is_empty() {
+                        println!(" no item type is in every rucksack");
+                    } else {
+                        let list = items
+                            .iter()
+                            .map(|item| format!("{item:?}"))
+                            .collect::<Vec<_>>();
+                        println!(" in every rucksack: {}", list.join(", "));
+                    }
+                }
+                QueryCommand::Most { item } => match inventory.most_of(item) {
+                    Some((line, count)) => println!(" line {line} has the most {item:?}: {count}"),
+                    None => println!(" no rucksack has {item:?}"),
+                },
+            }
+        }
+        Day3Command::Rebalance { inventory } => {
+            let (alphabet, input) = inventory.load()?;
+            let plan = day3::rebalance(&input, &alphabet)?;
+            println!("== day3: rebalancing plan ==");
+            for rucksack in &plan.rucksacks {
+                let moves = rucksack
+                    .moves
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>();
+                println!(
+                    " line {}: {} (cost {})",
+                    rucksack.line,
+                    moves.join(", "),
+                    rucksack.cost
+                );
+            }
+            println!(" total: {} items moved, cost {}", plan.moved(), plan.cost());
+        }
+        Day3Command::Groups {
+            inventory,
+            layout,
+            limit,
+        } => {
+            let (alphabet, input) = inventory.load()?;
+            let layout = layout.layout()?;
+            let partitions = day3::discover_groups(&input, &alphabet, &layout, limit)?;
+            println!("== day3: groups of {} ==", layout.group_size());
+            for (index, partition) in partitions.iter().enumerate() {
+                println!(" partition {} (score {}):", index + 1, partition.score());
+                for group in &partition.groups {
+                    let lines = group
+                        .lines
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>();
+                    println!(
+                        "  lines {}: badge {:?} ({})",
+                        lines.join(", "),
+                        group.badge,
+                        group.priority
+                    );
+                }
+            }
+        }
+    }
+
+    println!();
+    Ok(())
+}
+
+/// Read the puzzle input from the file at `path`, or from stdin if `path` is `-`.
+fn read_input(path: &Path) -> Result<String, Report> {
+    if path == Path::new("-") {
+        let mut input = String::new();
+        io::stdin()
+            .read_to_string(&mut input)
+            .wrap_err("read input from stdin")?;
+        Ok(input)
+    } else {
+        fs::read_to_string(path).wrap_err_with(|| format!("read input from {path:?}"))
+    }
+}
+
+/// An inclusive range of days to run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Days(RangeInclusive<u8>);
+
+impl Days {
+    /// The days that currently have solutions.
+    fn all() -> Self {
+        let first = SOLUTIONS.first().map(|s| s.day()).unwrap_or(1);
+        let last = SOLUTIONS.last().map(|s| s.day()).unwrap_or(first);
+        Self(first..=last)
+    }
+
+    /// Whether the range consists of only a single day.
+    fn is_single(&self) -> bool {
+        self.0.start() == self.0.end()
+    }
+}
+
+impl FromStr for Days {
+    type Err = Report;
+
+    /// Days are given either as a single day (`2`) or as an inclusive range (`1-3`).
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let parse_day = |day: &str| -> Result<u8, Report> {
+            let day = day
+                .trim()
+                .parse()
+                .wrap_err_with(|| format!("parse day '{day}'"))?;
+            ensure!(
+                (1..=25).contains(&day),
+                "day must be between 1 and 25, got {day}"
+            );
+            Ok(day)
+        };
+
+        let (start, end) = match input.split_once('-') {
+            Some((start, end)) => (parse_day(start)?, parse_day(end)?),
+            None => {
+                let day = parse_day(input)?;
+                (day, day)
+            }
+        };
+
+        ensure!(
+            start <= end,
+            "range of days must be ascending, got '{input}'"
+        );
+        Ok(Self(start..=end))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_days() -> Result<(), Report> {
+        assert_eq!(Days::from_str("2")?, Days(2..=2));
+        assert_eq!(Days::from_str("1-3")?, Days(1..=3));
+        assert!(Days::from_str("3-1").is_err());
+        assert!(Days::from_str("0").is_err());
+        assert!(Days::from_str("26").is_err());
+        assert!(Days::from_str("two").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn cli_parses() {
+        use clap::CommandFactory;
+        Cli::command().debug_assert();
+    }
+}
@@ -0,0 +1,62 @@
+use stable_eyre::Report;
+
+use crate::{day1, day2, day3, Answer};
+
+/// Every implemented day, in order.
+///
+/// Adding a new day means implementing [`Solution`] for it and listing it here.
+pub static SOLUTIONS: &[&dyn Solution] = &[&day1::Day1, &day2::Day2, &day3::Day3];
+
+/// A solution to a single day's puzzle.
+pub trait Solution: Sync {
+    /// The day of the advent calendar this solution is for.
+    fn day(&self) -> u8;
+
+    /// The title of the puzzle.
+    fn title(&self) -> &'static str;
+
+    /// The puzzle input that is built into the binary.
+    fn input(&self) -> &'static str;
+
+    /// Solve the first part of the puzzle for the given input.
+    fn part1(&self, input: &str) -> Result<Answer, Report>;
+
+    /// Solve the second part of the puzzle for the given input.
+    fn part2(&self, input: &str) -> Result<Answer, Report>;
+}
+
+/// Find the solution for the given day, if it has been implemented.
+pub fn find(day: u8) -> Option<&'static dyn Solution> {
+    SOLUTIONS
+        .iter()
+        .copied()
+        .find(|solution| solution.day() == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_is_ordered() {
+        let days = SOLUTIONS.iter().map(|s| s.day()).collect::<Vec<_>>();
+        let expected = (1..=days.len() as u8).collect::<Vec<_>>();
+        assert_eq!(days, expected);
+    }
+
+    #[test]
+    fn registry_solves_inputs() -> Result<(), Report> {
+        let expected = [(69528, 206152), (11386, 13600), (7875, 2479)];
+        for (solution, (part1, part2)) in SOLUTIONS.iter().zip(expected) {
+            assert_eq!(solution.part1(solution.input())?, part1);
+            assert_eq!(solution.part2(solution.input())?, part2);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn find_day() {
+        assert_eq!(find(2).map(|s| s.title()), Some("Rock Paper Scissors"));
+        assert!(find(25).is_none());
+    }
+}